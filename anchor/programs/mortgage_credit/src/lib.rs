@@ -1,8 +1,27 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, MintTo, SetAuthority, Token, TokenAccount, Transfer};
+use property_tokenization::Property;
 
 declare_id!("844W813eCyb4cAsUV6yC7P4Vc7AYAP1KHJiFBkBy1vcq");
 
+pub const EXCHANGE_RATE_SCALE: u64 = 1_000_000;
+pub const AMORTIZATION_SCALE: u128 = 1_000_000_000_000;
+pub const SECONDS_PER_YEAR: u64 = 365 * 86400;
+pub const INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+pub const DEFAULT_KINK_UTILIZATION: u64 = 8000;
+pub const DEFAULT_MISSED_PAYMENTS: u32 = 3;
+pub const AUCTION_DURATION: i64 = 3 * 86400;
+pub const MAX_CREDIT_SCORE: u16 = 850;
+pub const MAX_PREPAYMENT_TIERS: usize = 4;
+pub const PARAMETER_TIMELOCK: i64 = 2 * 86400;
+pub const MAX_AUCTION_ROUNDS: u8 = 3;
+pub const MAX_TERM_PERIODS: u32 = 480; // 40 years of monthly installments
+pub const MAX_LOAN_DURATION: u64 = 40 * SECONDS_PER_YEAR;
+pub const MAX_BORROW_RATE: u64 = 10000; // basis points
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+pub const MAX_PRICE_AGE: i64 = 86400;
+
 #[program]
 pub mod mortgage_credit {
     use super::*;
@@ -17,13 +36,59 @@ pub mod mortgage_credit {
     ) -> Result<()> {
         let pool = &mut ctx.accounts.mortgage_pool;
 
+        require!(interest_rate <= MAX_BORROW_RATE, MortgageError::InvalidPoolParameters);
+
         pool.authority = ctx.accounts.authority.key();
         pool.pool_name = pool_name;
         pool.interest_rate = interest_rate;
         pool.max_ltv = max_ltv;
         pool.min_credit_score = min_credit_score;
+        pool.sol_price = 0;
+        pool.sol_price_updated_at = 0;
         pool.total_liquidity = 0;
         pool.total_borrowed = 0;
+        pool.usdc_mint = ctx.accounts.usdc_mint.key();
+        pool.pool_vault = ctx.accounts.pool_vault.key();
+        pool.share_mint = ctx.accounts.share_mint.key();
+        pool.total_shares = 0;
+        pool.exchange_rate = EXCHANGE_RATE_SCALE;
+        pool.base_rate = interest_rate;
+        pool.rate_slope = 0;
+        pool.jump_rate_slope = 0;
+        pool.kink_utilization = DEFAULT_KINK_UTILIZATION;
+        pool.borrow_index = INDEX_SCALE;
+        pool.last_index_update = Clock::get()?.unix_timestamp;
+        pool.fixed_rate_weight = 0;
+        pool.fixed_weighted_start = 0;
+        pool.variable_balance = 0;
+        pool.variable_scaled_balance = 0;
+        pool.carried_interest = 0;
+        pool.interest_policy = InterestPolicy::Accrue;
+        pool.prepayment_tiers = Vec::new();
+        pool.reserve_vault = ctx.accounts.reserve_vault.key();
+        pool.reserve_factor_bps = 0;
+        pool.protocol_fee_bps = 0;
+        pool.protocol_treasury = None;
+        pool.total_reserves = 0;
+        pool.total_protocol_fees = 0;
+        pool.uncovered_losses = 0;
+        pool.tranches_enabled = false;
+        pool.junior_share_mint = Pubkey::default();
+        pool.junior_shares = 0;
+        pool.junior_exchange_rate = EXCHANGE_RATE_SCALE;
+        pool.senior_assets = 0;
+        pool.senior_target_rate = 0;
+        pool.min_subordination_bps = 0;
+        pool.senior_interest_owed = 0;
+        pool.senior_written_down = 0;
+        pool.last_senior_accrual = 0;
+        pool.pending_risk_params = None;
+        pool.pending_rate_model = None;
+        pool.pending_interest_policy = None;
+        pool.pending_reserve_config = None;
+        pool.pending_prepayment_tiers = None;
+        pool.pending_params_effective_at = 0;
+        pool.is_winding_down = false;
         pool.is_active = true;
         pool.created_at = Clock::get()?.unix_timestamp;
         pool.bump = *ctx.bumps.get("mortgage_pool").unwrap();
@@ -32,14 +97,364 @@ pub mod mortgage_credit {
         Ok(())
     }
 
-    /// Deposit liquidity into mortgage pool
+    /// Schedule new risk parameters, which take effect after the timelock
+    pub fn propose_parameter_update(
+        ctx: Context<UpdatePoolSettings>,
+        interest_rate: u64,
+        max_ltv: u64,
+        min_credit_score: u16,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(interest_rate <= MAX_BORROW_RATE, MortgageError::InvalidPoolParameters);
+        require!(max_ltv <= 10000, MortgageError::InvalidPoolParameters);
+        require!(min_credit_score <= MAX_CREDIT_SCORE, MortgageError::InvalidPoolParameters);
+
+        pool.pending_risk_params = Some(RiskParams { interest_rate, max_ltv, min_credit_score });
+        restart_parameter_timelock(pool)?;
+
+        msg!(
+            "Parameter update scheduled for {}: rate {} bps, max LTV {} bps, min credit score {}",
+            pool.pending_params_effective_at,
+            interest_rate,
+            max_ltv,
+            min_credit_score
+        );
+        Ok(())
+    }
+
+    /// Apply every scheduled pool setting once the timelock has passed
+    pub fn apply_parameter_update(
+        ctx: Context<ApplyParameterUpdate>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(pool.pending_params_effective_at > 0, MortgageError::NoPendingParameterUpdate);
+        require!(current_time >= pool.pending_params_effective_at, MortgageError::ParameterUpdateTimelocked);
+
+        if let Some(params) = pool.pending_risk_params.take() {
+            pool.interest_rate = params.interest_rate;
+            pool.max_ltv = params.max_ltv;
+            pool.min_credit_score = params.min_credit_score;
+        }
+
+        if let Some(model) = pool.pending_rate_model.take() {
+            // Close out accrual at the old rate before switching models
+            accrue_borrow_index(pool, current_time);
+
+            pool.base_rate = model.base_rate;
+            pool.rate_slope = model.rate_slope;
+            pool.jump_rate_slope = model.jump_rate_slope;
+            pool.kink_utilization = model.kink_utilization;
+        }
+
+        if let Some(interest_policy) = pool.pending_interest_policy.take() {
+            pool.interest_policy = interest_policy;
+        }
+
+        if let Some(config) = pool.pending_reserve_config.take() {
+            pool.reserve_factor_bps = config.reserve_factor_bps;
+            pool.protocol_fee_bps = config.protocol_fee_bps;
+            pool.protocol_treasury = config.protocol_treasury;
+        }
+
+        if let Some(prepayment_tiers) = pool.pending_prepayment_tiers.take() {
+            pool.prepayment_tiers = prepayment_tiers;
+        }
+
+        pool.pending_params_effective_at = 0;
+
+        msg!(
+            "Parameters updated: rate {} bps, max LTV {} bps, min credit score {}, borrow rate {} bps",
+            pool.interest_rate,
+            pool.max_ltv,
+            pool.min_credit_score,
+            current_borrow_rate(pool)
+        );
+        Ok(())
+    }
+
+    /// Cancel every scheduled pool setting
+    pub fn cancel_parameter_update(
+        ctx: Context<UpdatePoolSettings>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(pool.pending_params_effective_at > 0, MortgageError::NoPendingParameterUpdate);
+
+        clear_pending_parameters(pool);
+
+        msg!("Parameter update cancelled");
+        Ok(())
+    }
+
+    /// Pause or resume new deposits and originations; repayments and withdrawals continue
+    pub fn set_pool_paused(
+        ctx: Context<UpdatePoolSettings>,
+        paused: bool,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(!pool.is_winding_down, MortgageError::PoolWindingDown);
+
+        pool.is_active = !paused;
+
+        msg!("Mortgage pool {}", if paused { "paused" } else { "resumed" });
+        Ok(())
+    }
+
+    /// Permanently stop new business so the pool can run off its loans and be closed
+    pub fn wind_down_pool(
+        ctx: Context<UpdatePoolSettings>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(!pool.is_winding_down, MortgageError::PoolWindingDown);
+
+        pool.is_winding_down = true;
+        pool.is_active = false;
+        clear_pending_parameters(pool);
+
+        msg!("Mortgage pool {} winding down", pool.pool_name);
+        Ok(())
+    }
+
+    /// Close a wound-down pool once all loans are settled and all shares redeemed
+    pub fn close_mortgage_pool(
+        ctx: Context<CloseMortgagePool>,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.mortgage_pool;
+
+        require!(pool.is_winding_down, MortgageError::PoolNotWindingDown);
+        require!(pool.total_borrowed == 0, MortgageError::PoolHasOpenLoans);
+        require!(pool.total_shares == 0 && pool.junior_shares == 0, MortgageError::PoolHasOutstandingShares);
+
+        let pool_seeds: &[&[&[u8]]] = &[&[
+            b"mortgage_pool",
+            pool.authority.as_ref(),
+            pool.pool_name.as_bytes(),
+            &[pool.bump],
+        ]];
+
+        // Sweep rounding dust and leftover reserves to the authority, then close both vaults
+        for vault in [&ctx.accounts.pool_vault, &ctx.accounts.reserve_vault] {
+            if vault.amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: vault.to_account_info(),
+                            to: ctx.accounts.authority_token_account.to_account_info(),
+                            authority: pool.to_account_info(),
+                        },
+                        pool_seeds,
+                    ),
+                    vault.amount,
+                )?;
+            }
+
+            token::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: vault.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                pool_seeds,
+            ))?;
+        }
+
+        msg!("Mortgage pool {} closed", pool.pool_name);
+        Ok(())
+    }
+
+    /// Split a pool without liquidity yet into senior and junior tranches; the pool share mint becomes the senior mint
+    pub fn enable_tranches(
+        ctx: Context<EnableTranches>,
+        senior_target_rate: u64,   // basis points per year
+        min_subordination_bps: u64, // junior assets junior withdrawals must leave, relative to senior assets
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(!pool.tranches_enabled, MortgageError::TranchesAlreadyEnabled);
+        require!(pool.total_shares == 0, MortgageError::PoolHasLiquidity);
+        require!(min_subordination_bps <= 10000, MortgageError::InvalidPoolParameters);
+
+        pool.tranches_enabled = true;
+        pool.junior_share_mint = ctx.accounts.junior_share_mint.key();
+        pool.senior_target_rate = senior_target_rate;
+        pool.min_subordination_bps = min_subordination_bps;
+        pool.last_senior_accrual = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "Tranches enabled, senior target rate {} bps, minimum subordination {} bps",
+            senior_target_rate,
+            min_subordination_bps
+        );
+        Ok(())
+    }
+
+    /// Schedule a new utilization-based rate model for variable-rate mortgages
+    pub fn set_rate_model(
+        ctx: Context<UpdatePoolSettings>,
+        base_rate: u64,        // basis points at zero utilization
+        rate_slope: u64,       // basis points added between zero and kink utilization
+        jump_rate_slope: u64,  // basis points added between kink and full utilization
+        kink_utilization: u64, // basis points
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(kink_utilization > 0 && kink_utilization < 10000, MortgageError::InvalidRateModel);
+        require!(
+            base_rate
+                .checked_add(rate_slope)
+                .and_then(|rate| rate.checked_add(jump_rate_slope))
+                .is_some_and(|max_rate| max_rate <= MAX_BORROW_RATE),
+            MortgageError::InvalidRateModel
+        );
+
+        pool.pending_rate_model = Some(RateModel { base_rate, rate_slope, jump_rate_slope, kink_utilization });
+        restart_parameter_timelock(pool)?;
+
+        msg!("Rate model update scheduled for {}", pool.pending_params_effective_at);
+        Ok(())
+    }
+
+    /// Schedule whether unpaid interest is carried separately or capitalized into principal
+    pub fn set_interest_policy(
+        ctx: Context<UpdatePoolSettings>,
+        interest_policy: InterestPolicy,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        pool.pending_interest_policy = Some(interest_policy);
+        restart_parameter_timelock(pool)?;
+
+        msg!("Interest policy update scheduled for {}", pool.pending_params_effective_at);
+        Ok(())
+    }
+
+    /// Schedule the share of interest diverted to the pool reserve and to the protocol treasury
+    pub fn set_reserve_config(
+        ctx: Context<UpdatePoolSettings>,
+        reserve_factor_bps: u16,
+        protocol_fee_bps: u16,
+        protocol_treasury: Option<Pubkey>, // token account receiving protocol fees
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(
+            (reserve_factor_bps as u32) + (protocol_fee_bps as u32) <= 10000,
+            MortgageError::InvalidReserveConfig
+        );
+        require!(protocol_fee_bps == 0 || protocol_treasury.is_some(), MortgageError::InvalidReserveConfig);
+
+        pool.pending_reserve_config = Some(ReserveConfig { reserve_factor_bps, protocol_fee_bps, protocol_treasury });
+        restart_parameter_timelock(pool)?;
+
+        msg!(
+            "Reserve factor {} bps, protocol fee {} bps scheduled for {}",
+            reserve_factor_bps,
+            protocol_fee_bps,
+            pool.pending_params_effective_at
+        );
+        Ok(())
+    }
+
+    /// Use pool reserves to make liquidity providers whole for written-off loans
+    pub fn cover_losses(
+        ctx: Context<CoverLosses>,
+        amount: u64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(amount <= pool.uncovered_losses, MortgageError::ExceedsUncoveredLosses);
+        require!(amount <= ctx.accounts.reserve_vault.amount, MortgageError::InsufficientReserves);
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.reserve_vault.to_account_info(),
+                    to: ctx.accounts.pool_vault.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                &[&[
+                    b"mortgage_pool",
+                    pool.authority.as_ref(),
+                    pool.pool_name.as_bytes(),
+                    &[pool.bump],
+                ]],
+            ),
+            amount,
+        )?;
+
+        pool.total_liquidity = pool.total_liquidity.checked_add(amount).unwrap();
+        pool.total_reserves = pool.total_reserves.saturating_sub(amount);
+        recover_losses(pool, amount);
+        update_exchange_rate(pool);
+
+        msg!("Covered {} USDC of losses from reserves", amount);
+        Ok(())
+    }
+
+    /// Schedule a new prepayment penalty schedule, ordered by increasing loan age
+    pub fn set_prepayment_penalties(
+        ctx: Context<UpdatePoolSettings>,
+        prepayment_tiers: Vec<PrepaymentTier>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(prepayment_tiers.len() <= MAX_PREPAYMENT_TIERS, MortgageError::TooManyPrepaymentTiers);
+        for (i, tier) in prepayment_tiers.iter().enumerate() {
+            require!(tier.penalty_bps <= 10000, MortgageError::InvalidPrepaymentTier);
+            require!(
+                i == 0 || tier.max_loan_age > prepayment_tiers[i - 1].max_loan_age,
+                MortgageError::InvalidPrepaymentTier
+            );
+        }
+
+        let tier_count = prepayment_tiers.len();
+        pool.pending_prepayment_tiers = Some(prepayment_tiers);
+        restart_parameter_timelock(pool)?;
+
+        msg!("Prepayment penalty schedule of {} tiers scheduled for {}", tier_count, pool.pending_params_effective_at);
+        Ok(())
+    }
+
+    /// Deposit liquidity into mortgage pool in exchange for pool shares
     pub fn deposit_liquidity(
         ctx: Context<DepositLiquidity>,
         amount: u64,
+        tranche: Tranche,
     ) -> Result<()> {
         let pool = &mut ctx.accounts.mortgage_pool;
+        let current_time = Clock::get()?.unix_timestamp;
 
         require!(pool.is_active, MortgageError::InactivePool);
+        require!(pool.tranches_enabled || tranche == Tranche::Senior, MortgageError::TranchesNotEnabled);
+        require_keys_eq!(ctx.accounts.share_mint.key(), tranche_mint(pool, &tranche), MortgageError::InvalidShareMint);
+
+        accrue_borrow_index(pool, current_time);
+        accrue_senior_interest(pool, 0, current_time);
+
+        // Shares are priced at the tranche's exchange rate; the first deposit mints 1:1.
+        // A tranche wiped out by losses can't take deposits, or they'd be shared with its holders
+        let tranche_assets = tranche_assets(pool, &tranche);
+        let tranche_shares = tranche_shares(pool, &tranche);
+        let shares = if tranche_shares == 0 {
+            amount
+        } else {
+            require!(tranche_assets > 0, MortgageError::TrancheInsolvent);
+
+            (amount as u128)
+                .checked_mul(tranche_shares as u128)
+                .unwrap()
+                .checked_div(tranche_assets as u128)
+                .unwrap() as u64
+        };
+        require!(shares > 0, MortgageError::ZeroShares);
 
         // Transfer USDC to pool
         token::transfer(
@@ -47,35 +462,213 @@ pub mod mortgage_credit {
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.depositor_token_account.to_account_info(),
-                    to: ctx.accounts.pool_token_account.to_account_info(),
+                    to: ctx.accounts.pool_vault.to_account_info(),
                     authority: ctx.accounts.depositor.to_account_info(),
                 },
             ),
             amount,
         )?;
 
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                &[&[
+                    b"mortgage_pool",
+                    pool.authority.as_ref(),
+                    pool.pool_name.as_bytes(),
+                    &[pool.bump],
+                ]],
+            ),
+            shares,
+        )?;
+
         pool.total_liquidity = pool.total_liquidity.checked_add(amount).unwrap();
+        match tranche {
+            Tranche::Senior => {
+                pool.total_shares = pool.total_shares.checked_add(shares).unwrap();
+                if pool.tranches_enabled {
+                    pool.senior_assets = pool.senior_assets.checked_add(amount).unwrap();
+                }
+            }
+            Tranche::Junior => {
+                pool.junior_shares = pool.junior_shares.checked_add(shares).unwrap();
+            }
+        }
+        update_exchange_rate(pool);
+
+        msg!("Deposited {} USDC to pool for {} shares", amount, shares);
+        Ok(())
+    }
+
+    /// Burn pool shares for a pro-rata cut of the pool's assets, paid from available cash
+    pub fn withdraw_liquidity(
+        ctx: Context<WithdrawLiquidity>,
+        shares: u64,
+        tranche: Tranche,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(shares > 0 && shares <= tranche_shares(pool, &tranche), MortgageError::ZeroShares);
+        require_keys_eq!(ctx.accounts.share_mint.key(), tranche_mint(pool, &tranche), MortgageError::InvalidShareMint);
+
+        accrue_borrow_index(pool, current_time);
+        accrue_senior_interest(pool, 0, current_time);
+
+        let amount = (shares as u128)
+            .checked_mul(tranche_assets(pool, &tranche) as u128)
+            .unwrap()
+            .checked_div(tranche_shares(pool, &tranche) as u128)
+            .unwrap() as u64;
+        require!(amount <= pool.total_liquidity, MortgageError::InsufficientLiquidity);
+
+        // Junior capital must keep cushioning the senior tranche while senior shares are outstanding
+        if tranche == Tranche::Junior && pool.total_shares > 0 {
+            let junior_remaining = tranche_assets(pool, &Tranche::Junior).checked_sub(amount).unwrap();
+            require!(
+                (junior_remaining as u128).checked_mul(10000).unwrap()
+                    >= (pool.senior_assets as u128).checked_mul(pool.min_subordination_bps as u128).unwrap(),
+                MortgageError::SubordinationBreached
+            );
+        }
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.withdrawer_share_account.to_account_info(),
+                    authority: ctx.accounts.withdrawer.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.pool_vault.to_account_info(),
+                    to: ctx.accounts.withdrawer_token_account.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                &[&[
+                    b"mortgage_pool",
+                    pool.authority.as_ref(),
+                    pool.pool_name.as_bytes(),
+                    &[pool.bump],
+                ]],
+            ),
+            amount,
+        )?;
+
+        pool.total_liquidity = pool.total_liquidity.checked_sub(amount).unwrap();
+        match tranche {
+            Tranche::Senior => {
+                pool.total_shares = pool.total_shares.checked_sub(shares).unwrap();
+                if pool.tranches_enabled {
+                    pool.senior_assets = pool.senior_assets.saturating_sub(amount);
+                }
+            }
+            Tranche::Junior => {
+                pool.junior_shares = pool.junior_shares.checked_sub(shares).unwrap();
+            }
+        }
+        update_exchange_rate(pool);
+
+        msg!("Withdrew {} USDC for {} shares", amount, shares);
+        Ok(())
+    }
+
+    /// Register a credit bureau whose attestations the pool accepts
+    pub fn add_credit_bureau(
+        ctx: Context<AddCreditBureau>,
+        bureau_name: String,
+    ) -> Result<()> {
+        let bureau = &mut ctx.accounts.credit_bureau;
+
+        require!(bureau_name.len() <= 50, MortgageError::NameTooLong);
+
+        bureau.pool = ctx.accounts.mortgage_pool.key();
+        bureau.bureau = ctx.accounts.bureau_address.key();
+        bureau.bureau_name = bureau_name;
+        bureau.is_active = true;
+        bureau.added_at = Clock::get()?.unix_timestamp;
+        bureau.bump = *ctx.bumps.get("credit_bureau").unwrap();
+
+        msg!("Credit bureau registered: {}", bureau.bureau_name);
+        Ok(())
+    }
+
+    /// Stop accepting a credit bureau's attestations
+    pub fn remove_credit_bureau(
+        ctx: Context<RemoveCreditBureau>,
+    ) -> Result<()> {
+        let bureau = &mut ctx.accounts.credit_bureau;
+
+        bureau.is_active = false;
+
+        msg!("Credit bureau removed: {}", bureau.bureau_name);
+        Ok(())
+    }
 
-        msg!("Deposited {} USDC to pool", amount);
+    /// Issue or refresh a borrower's credit score attestation
+    pub fn issue_credit_attestation(
+        ctx: Context<IssueCreditAttestation>,
+        credit_score: u16,
+        valid_for: i64, // seconds
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.credit_attestation;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(ctx.accounts.credit_bureau.is_active, MortgageError::InactiveCreditBureau);
+        require!(credit_score <= MAX_CREDIT_SCORE, MortgageError::InvalidCreditScore);
+        require!(valid_for > 0, MortgageError::InvalidAttestationExpiry);
+
+        attestation.bureau = ctx.accounts.bureau.key();
+        attestation.borrower = ctx.accounts.borrower.key();
+        attestation.credit_score = credit_score;
+        attestation.issued_at = current_time;
+        attestation.expires_at = current_time.checked_add(valid_for).unwrap();
+        attestation.bump = *ctx.bumps.get("credit_attestation").unwrap();
+
+        msg!("Credit score {} attested for {}", credit_score, attestation.borrower);
         Ok(())
     }
 
-    /// Request a mortgage loan backed by property tokens
+    /// Request a mortgage loan backed by property tokens locked in escrow
     pub fn request_mortgage(
         ctx: Context<RequestMortgage>,
-        property_mint: Pubkey,
         loan_amount: u64,
         collateral_shares: u64,
-        credit_score: u16,
+        term_periods: u32,
+        payment_frequency: u64, // in seconds (e.g., 30 days = 2592000)
+        rate_type: RateType,
     ) -> Result<()> {
         let pool = &ctx.accounts.mortgage_pool;
         let mortgage = &mut ctx.accounts.mortgage;
 
+        // Credit score comes from an unexpired attestation by a bureau the pool accepts
+        let attestation = &ctx.accounts.credit_attestation;
+        let credit_score = attestation.credit_score;
+
         require!(pool.is_active, MortgageError::InactivePool);
+        require!(ctx.accounts.credit_bureau.is_active, MortgageError::InactiveCreditBureau);
+        require!(Clock::get()?.unix_timestamp < attestation.expires_at, MortgageError::AttestationExpired);
         require!(credit_score >= pool.min_credit_score, MortgageError::InsufficientCreditScore);
+        require!(ctx.accounts.property.is_tokenized, MortgageError::PropertyNotTokenized);
+        require!(collateral_shares > 0, MortgageError::ZeroShares);
+        require!(valid_loan_terms(term_periods, payment_frequency), MortgageError::InvalidLoanTerms);
 
-        // Calculate max loan based on LTV
-        let max_loan = (collateral_shares as u128)
+        // Calculate max loan based on LTV against the shares' market value
+        require!(price_is_fresh(pool, Clock::get()?.unix_timestamp), MortgageError::StaleCollateralPrice);
+        let collateral_value = collateral_value(&ctx.accounts.property, collateral_shares, pool);
+        let max_loan = (collateral_value as u128)
             .checked_mul(pool.max_ltv as u128)
             .unwrap()
             .checked_div(10000)
@@ -83,200 +676,2276 @@ pub mod mortgage_credit {
 
         require!(loan_amount <= max_loan, MortgageError::ExceedsMaxLTV);
 
+        // Lock the borrower's property shares in the mortgage escrow
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.borrower_share_account.to_account_info(),
+                    to: ctx.accounts.collateral_escrow.to_account_info(),
+                    authority: ctx.accounts.borrower.to_account_info(),
+                },
+            ),
+            collateral_shares,
+        )?;
+
+        let borrower_account = &mut ctx.accounts.borrower_account;
+        borrower_account.borrower = ctx.accounts.borrower.key();
+        borrower_account.pool = pool.key();
+        borrower_account.bump = *ctx.bumps.get("borrower_account").unwrap();
+
         mortgage.borrower = ctx.accounts.borrower.key();
         mortgage.pool = pool.key();
-        mortgage.property_mint = property_mint;
+        mortgage.loan_index = borrower_account.loan_count;
+        mortgage.property = ctx.accounts.property.key();
+        mortgage.property_mint = ctx.accounts.property_mint.key();
+        mortgage.collateral_escrow = ctx.accounts.collateral_escrow.key();
         mortgage.loan_amount = loan_amount;
         mortgage.collateral_shares = collateral_shares;
+        mortgage.collateral_value = collateral_value;
+        mortgage.current_ltv = loan_to_value(loan_amount, collateral_value);
+        mortgage.ltv_breached = false;
+        mortgage.last_valuation = Clock::get()?.unix_timestamp;
+        mortgage.rate_type = rate_type;
         mortgage.interest_rate = pool.interest_rate;
+        mortgage.borrow_index_snapshot = 0;
+        mortgage.accrued_interest = 0;
+        mortgage.capitalized_interest = 0;
         mortgage.outstanding_balance = loan_amount;
         mortgage.credit_score = credit_score;
+        mortgage.credit_attestation = attestation.key();
+        mortgage.term_periods = term_periods;
+        mortgage.payment_frequency = payment_frequency;
+        mortgage.installment_amount = 0;
+        mortgage.next_due_date = 0;
+        mortgage.installments_paid = 0;
+        mortgage.missed_payments = 0;
+        mortgage.total_paid = 0;
+        mortgage.delinquency = DelinquencyStatus::Current;
+        mortgage.status = MortgageStatus::Requested;
+        mortgage.approved_by = None;
+        mortgage.approved_at = 0;
+        mortgage.rejection_reason = None;
         mortgage.is_active = true;
         mortgage.start_date = Clock::get()?.unix_timestamp;
         mortgage.last_payment_date = 0;
         mortgage.bump = *ctx.bumps.get("mortgage").unwrap();
 
-        msg!("Mortgage requested: {} USDC at {}%", loan_amount, pool.interest_rate / 100);
-        Ok(())
-    }
+        borrower_account.loan_count = borrower_account.loan_count.checked_add(1).unwrap();
+
+        msg!("Mortgage requested: {} USDC at {}%", loan_amount, pool.interest_rate / 100);
+        Ok(())
+    }
+
+    /// Register an underwriter allowed to approve and reject mortgages for the pool
+    pub fn add_underwriter(
+        ctx: Context<AddUnderwriter>,
+    ) -> Result<()> {
+        let underwriter = &mut ctx.accounts.underwriter;
+
+        underwriter.pool = ctx.accounts.mortgage_pool.key();
+        underwriter.underwriter = ctx.accounts.underwriter_address.key();
+        underwriter.is_active = true;
+        underwriter.added_at = Clock::get()?.unix_timestamp;
+        underwriter.bump = *ctx.bumps.get("underwriter").unwrap();
+
+        msg!("Underwriter {} added", underwriter.underwriter);
+        Ok(())
+    }
+
+    /// Revoke an underwriter
+    pub fn remove_underwriter(
+        ctx: Context<RemoveUnderwriter>,
+    ) -> Result<()> {
+        let underwriter = &mut ctx.accounts.underwriter;
+
+        underwriter.is_active = false;
+
+        msg!("Underwriter {} removed", underwriter.underwriter);
+        Ok(())
+    }
+
+    /// Record the SOL price in the pool's quote asset for valuing property shares
+    pub fn set_collateral_price(
+        ctx: Context<SetCollateralPrice>,
+        sol_price: u64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(
+            can_underwrite(pool, ctx.accounts.underwriter.as_deref(), ctx.accounts.signer.key()),
+            MortgageError::Unauthorized
+        );
+        require!(sol_price > 0, MortgageError::InvalidCollateralPrice);
+
+        pool.sol_price = sol_price;
+        pool.sol_price_updated_at = Clock::get()?.unix_timestamp;
+
+        msg!("Collateral price set to {} per SOL", sol_price);
+        Ok(())
+    }
+
+    /// Underwrite and approve a requested mortgage
+    pub fn approve_mortgage(
+        ctx: Context<ApproveMortgage>,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.mortgage_pool;
+        let mortgage = &mut ctx.accounts.mortgage;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(
+            can_underwrite(pool, ctx.accounts.underwriter.as_deref(), ctx.accounts.approver.key()),
+            MortgageError::Unauthorized
+        );
+        require!(mortgage.status == MortgageStatus::Requested, MortgageError::InvalidMortgageStatus);
+        require!(pool.is_active, MortgageError::InactivePool);
+        require!(mortgage.credit_score >= pool.min_credit_score, MortgageError::InsufficientCreditScore);
+
+        // Collateral must still cover the loan at today's share price
+        require!(price_is_fresh(pool, current_time), MortgageError::StaleCollateralPrice);
+        refresh_ltv(mortgage, &ctx.accounts.property, pool, current_time);
+        require!(!mortgage.ltv_breached, MortgageError::ExceedsMaxLTV);
+
+        mortgage.status = MortgageStatus::Approved;
+        mortgage.approved_by = Some(ctx.accounts.approver.key());
+        mortgage.approved_at = current_time;
+
+        msg!("Mortgage approved by {}", ctx.accounts.approver.key());
+        Ok(())
+    }
+
+    /// Reject a pending mortgage and return the escrowed collateral
+    pub fn reject_mortgage(
+        ctx: Context<RejectMortgage>,
+        reason: String,
+    ) -> Result<()> {
+        let mortgage = &mut ctx.accounts.mortgage;
+
+        require!(
+            can_underwrite(&ctx.accounts.mortgage_pool, ctx.accounts.underwriter.as_deref(), ctx.accounts.approver.key()),
+            MortgageError::Unauthorized
+        );
+        require!(
+            mortgage.status == MortgageStatus::Requested || mortgage.status == MortgageStatus::Approved,
+            MortgageError::InvalidMortgageStatus
+        );
+        require!(reason.len() <= 200, MortgageError::ReasonTooLong);
+        require_keys_eq!(ctx.accounts.borrower_share_account.owner, mortgage.borrower, MortgageError::Unauthorized);
+
+        let loan_index = mortgage.loan_index.to_le_bytes();
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.collateral_escrow.to_account_info(),
+                    to: ctx.accounts.borrower_share_account.to_account_info(),
+                    authority: mortgage.to_account_info(),
+                },
+                &[&[
+                    b"mortgage",
+                    mortgage.borrower.as_ref(),
+                    mortgage.pool.as_ref(),
+                    loan_index.as_ref(),
+                    &[mortgage.bump],
+                ]],
+            ),
+            ctx.accounts.collateral_escrow.amount,
+        )?;
+
+        mortgage.status = MortgageStatus::Rejected;
+        mortgage.rejection_reason = Some(reason);
+        mortgage.is_active = false;
+
+        msg!("Mortgage rejected, collateral returned");
+        Ok(())
+    }
+
+    /// Withdraw a mortgage request before it is funded and reclaim the escrowed collateral
+    pub fn cancel_mortgage(
+        ctx: Context<CancelMortgage>,
+    ) -> Result<()> {
+        let mortgage = &mut ctx.accounts.mortgage;
+
+        require!(
+            mortgage.status == MortgageStatus::Requested || mortgage.status == MortgageStatus::Approved,
+            MortgageError::InvalidMortgageStatus
+        );
+
+        let loan_index = mortgage.loan_index.to_le_bytes();
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.collateral_escrow.to_account_info(),
+                    to: ctx.accounts.borrower_share_account.to_account_info(),
+                    authority: mortgage.to_account_info(),
+                },
+                &[&[
+                    b"mortgage",
+                    mortgage.borrower.as_ref(),
+                    mortgage.pool.as_ref(),
+                    loan_index.as_ref(),
+                    &[mortgage.bump],
+                ]],
+            ),
+            ctx.accounts.collateral_escrow.amount,
+        )?;
+
+        mortgage.status = MortgageStatus::Cancelled;
+        mortgage.is_active = false;
+
+        msg!("Mortgage cancelled by borrower, collateral returned");
+        Ok(())
+    }
+
+    /// Disburse an approved mortgage to the borrower; a mortgage can only be funded once
+    pub fn fund_mortgage(
+        ctx: Context<FundMortgage>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.mortgage_pool;
+        let mortgage = &mut ctx.accounts.mortgage;
+
+        require!(mortgage.status == MortgageStatus::Approved, MortgageError::InvalidMortgageStatus);
+        require!(pool.is_active, MortgageError::InactivePool);
+        require!(pool.total_liquidity >= mortgage.loan_amount, MortgageError::InsufficientLiquidity);
+        require_keys_eq!(ctx.accounts.borrower_token_account.owner, mortgage.borrower, MortgageError::Unauthorized);
+
+        // Transfer loan to borrower from the pool vault
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.pool_vault.to_account_info(),
+                    to: ctx.accounts.borrower_token_account.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                &[&[
+                    b"mortgage_pool",
+                    pool.authority.as_ref(),
+                    pool.pool_name.as_bytes(),
+                    &[pool.bump],
+                ]],
+            ),
+            mortgage.loan_amount,
+        )?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        accrue_borrow_index(pool, current_time);
+
+        pool.total_borrowed = pool.total_borrowed.checked_add(mortgage.loan_amount).unwrap();
+        pool.total_liquidity = pool.total_liquidity.checked_sub(mortgage.loan_amount).unwrap();
+
+        // Variable-rate loans start at the post-funding utilization rate and track the borrow index
+        if mortgage.rate_type == RateType::Variable {
+            mortgage.interest_rate = current_borrow_rate(pool);
+            mortgage.borrow_index_snapshot = pool.borrow_index;
+        }
+
+        // Amortization schedule starts at funding
+        mortgage.installment_amount = amortized_installment(mortgage);
+        mortgage.start_date = current_time;
+        mortgage.next_due_date = current_time.checked_add(mortgage.payment_frequency as i64).unwrap();
+        mortgage.status = MortgageStatus::Funded;
+
+        book_loan_interest(pool, mortgage);
+        update_exchange_rate(pool);
+
+        msg!(
+            "Mortgage funded: {} USDC, {} installments of {} USDC",
+            mortgage.loan_amount,
+            mortgage.term_periods,
+            mortgage.installment_amount
+        );
+        Ok(())
+    }
+
+    /// Pay the next scheduled mortgage installment
+    pub fn pay_mortgage(
+        ctx: Context<PayMortgage>,
+    ) -> Result<()> {
+        let mortgage = &mut ctx.accounts.mortgage;
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(mortgage.status == MortgageStatus::Funded, MortgageError::MortgageNotFunded);
+        require_keys_eq!(mortgage.borrower, ctx.accounts.borrower.key(), MortgageError::Unauthorized);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        accrue_borrow_index(pool, current_time);
+        unbook_loan_interest(pool, mortgage);
+
+        let interest = match mortgage.rate_type {
+            RateType::Fixed => period_interest(mortgage),
+            RateType::Variable => {
+                let interest = index_interest(mortgage, pool.borrow_index);
+                mortgage.borrow_index_snapshot = pool.borrow_index;
+
+                // Re-price the installment at the pool's current rate
+                mortgage.interest_rate = current_borrow_rate(pool);
+                mortgage.installment_amount = amortized_installment(mortgage);
+                interest
+            }
+        };
+
+        // Interest carried over from earlier shortfalls is settled before principal
+        let interest_due = mortgage.accrued_interest.checked_add(interest).unwrap();
+
+        // The final installment clears whatever balance rounding left behind
+        let amount_owed = mortgage.outstanding_balance.checked_add(interest_due).unwrap();
+        let payment_amount = if mortgage.installments_paid.checked_add(1).unwrap() >= mortgage.term_periods {
+            amount_owed
+        } else {
+            mortgage.installment_amount.min(amount_owed)
+        };
+        let is_late = current_time > mortgage.next_due_date;
+
+        let interest_paid = payment_amount.min(interest_due);
+        let principal_payment = payment_amount.checked_sub(interest_paid).unwrap();
+        let unpaid_interest = interest_due.checked_sub(interest_paid).unwrap();
+
+        // Part of the interest goes to the reserve and protocol treasury, the rest to the pool
+        let (reserve_cut, protocol_cut) = interest_fees(pool, interest_paid);
+        let pool_amount = payment_amount - reserve_cut - protocol_cut;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.borrower_token_account.to_account_info(),
+                    to: ctx.accounts.pool_vault.to_account_info(),
+                    authority: ctx.accounts.borrower.to_account_info(),
+                },
+            ),
+            pool_amount,
+        )?;
+
+        if reserve_cut > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.borrower_token_account.to_account_info(),
+                        to: ctx.accounts.reserve_vault.to_account_info(),
+                        authority: ctx.accounts.borrower.to_account_info(),
+                    },
+                ),
+                reserve_cut,
+            )?;
+        }
+
+        if protocol_cut > 0 {
+            let treasury = ctx
+                .accounts
+                .protocol_treasury
+                .as_ref()
+                .ok_or(MortgageError::InvalidProtocolTreasury)?;
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.borrower_token_account.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: ctx.accounts.borrower.to_account_info(),
+                    },
+                ),
+                protocol_cut,
+            )?;
+        }
+
+        // Update balances
+
+        mortgage.outstanding_balance = mortgage.outstanding_balance.saturating_sub(principal_payment);
+        mortgage.last_payment_date = current_time;
+        mortgage.installments_paid = mortgage.installments_paid.checked_add(1).unwrap();
+
+        match pool.interest_policy {
+            InterestPolicy::Accrue => {
+                mortgage.accrued_interest = unpaid_interest;
+            }
+            InterestPolicy::Capitalize => {
+                // Unpaid interest joins the principal and bears interest from here on
+                mortgage.accrued_interest = 0;
+                mortgage.outstanding_balance = mortgage.outstanding_balance.checked_add(unpaid_interest).unwrap();
+                mortgage.capitalized_interest = mortgage.capitalized_interest.checked_add(unpaid_interest).unwrap();
+                pool.total_borrowed = pool.total_borrowed.checked_add(unpaid_interest).unwrap();
+                if unpaid_interest > 0 {
+                    mortgage.installment_amount = amortized_installment(mortgage);
+                }
+            }
+        }
+
+        mortgage.total_paid = mortgage.total_paid.checked_add(payment_amount).unwrap();
+        mortgage.next_due_date = mortgage.next_due_date.checked_add(mortgage.payment_frequency as i64).unwrap();
+        pool.total_reserves = pool.total_reserves.checked_add(reserve_cut).unwrap();
+        pool.total_protocol_fees = pool.total_protocol_fees.checked_add(protocol_cut).unwrap();
+        mortgage.missed_payments = installments_due(mortgage, current_time).saturating_sub(mortgage.installments_paid);
+        mortgage.delinquency = if mortgage.missed_payments > 0 {
+            DelinquencyStatus::Late
+        } else {
+            DelinquencyStatus::Current
+        };
+        pool.total_liquidity = pool.total_liquidity.checked_add(pool_amount).unwrap();
+        pool.total_borrowed = pool.total_borrowed.saturating_sub(principal_payment);
+        if mortgage.outstanding_balance > 0 || mortgage.accrued_interest > 0 {
+            book_loan_interest(pool, mortgage);
+        }
+        accrue_senior_interest(pool, interest_paid - reserve_cut - protocol_cut, current_time);
+        update_exchange_rate(pool);
+
+        refresh_ltv(mortgage, &ctx.accounts.property, pool, current_time);
+
+        if mortgage.outstanding_balance == 0 && mortgage.accrued_interest == 0 {
+            mortgage.status = MortgageStatus::Repaid;
+            mortgage.is_active = false;
+
+            // Release the collateral once the loan is fully repaid
+            let loan_index = mortgage.loan_index.to_le_bytes();
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.collateral_escrow.to_account_info(),
+                        to: ctx.accounts.borrower_share_account.to_account_info(),
+                        authority: mortgage.to_account_info(),
+                    },
+                    &[&[
+                        b"mortgage",
+                        mortgage.borrower.as_ref(),
+                        mortgage.pool.as_ref(),
+                        loan_index.as_ref(),
+                        &[mortgage.bump],
+                    ]],
+                ),
+                ctx.accounts.collateral_escrow.amount,
+            )?;
+
+            msg!("Collateral of {} shares released", ctx.accounts.collateral_escrow.amount);
+        }
+
+        msg!(
+            "Installment {}/{}: {} USDC (Interest: {}, Principal: {}, Unpaid interest: {}){}",
+            mortgage.installments_paid,
+            mortgage.term_periods,
+            payment_amount,
+            interest_paid,
+            principal_payment,
+            unpaid_interest,
+            if is_late { " late" } else { "" }
+        );
+        Ok(())
+    }
+
+    /// Pay off the whole mortgage early, including any prepayment penalty, and release the collateral
+    pub fn repay_mortgage_in_full(
+        ctx: Context<RepayMortgageInFull>,
+    ) -> Result<()> {
+        let mortgage = &mut ctx.accounts.mortgage;
+        let pool = &mut ctx.accounts.mortgage_pool;
+
+        require!(mortgage.status == MortgageStatus::Funded, MortgageError::MortgageNotFunded);
+        require_keys_eq!(mortgage.borrower, ctx.accounts.borrower.key(), MortgageError::Unauthorized);
+        require_keys_eq!(ctx.accounts.borrower_share_account.owner, mortgage.borrower, MortgageError::Unauthorized);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        accrue_borrow_index(pool, current_time);
+        unbook_loan_interest(pool, mortgage);
+
+        let payoff = payoff_amount(mortgage, pool, current_time);
+        let penalty = prepayment_penalty(pool, mortgage, current_time);
+        let total = payoff.checked_add(penalty).unwrap();
+
+        let interest = payoff.checked_sub(mortgage.outstanding_balance).unwrap();
+        let (reserve_cut, protocol_cut) = interest_fees(pool, interest);
+        let pool_amount = total - reserve_cut - protocol_cut;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.borrower_token_account.to_account_info(),
+                    to: ctx.accounts.pool_vault.to_account_info(),
+                    authority: ctx.accounts.borrower.to_account_info(),
+                },
+            ),
+            pool_amount,
+        )?;
+
+        if reserve_cut > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.borrower_token_account.to_account_info(),
+                        to: ctx.accounts.reserve_vault.to_account_info(),
+                        authority: ctx.accounts.borrower.to_account_info(),
+                    },
+                ),
+                reserve_cut,
+            )?;
+        }
+
+        if protocol_cut > 0 {
+            let treasury = ctx
+                .accounts
+                .protocol_treasury
+                .as_ref()
+                .ok_or(MortgageError::InvalidProtocolTreasury)?;
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.borrower_token_account.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: ctx.accounts.borrower.to_account_info(),
+                    },
+                ),
+                protocol_cut,
+            )?;
+        }
+
+        let loan_index = mortgage.loan_index.to_le_bytes();
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.collateral_escrow.to_account_info(),
+                    to: ctx.accounts.borrower_share_account.to_account_info(),
+                    authority: mortgage.to_account_info(),
+                },
+                &[&[
+                    b"mortgage",
+                    mortgage.borrower.as_ref(),
+                    mortgage.pool.as_ref(),
+                    loan_index.as_ref(),
+                    &[mortgage.bump],
+                ]],
+            ),
+            ctx.accounts.collateral_escrow.amount,
+        )?;
+
+        pool.total_liquidity = pool.total_liquidity.checked_add(pool_amount).unwrap();
+        pool.total_borrowed = pool.total_borrowed.saturating_sub(mortgage.outstanding_balance);
+        pool.total_reserves = pool.total_reserves.checked_add(reserve_cut).unwrap();
+        pool.total_protocol_fees = pool.total_protocol_fees.checked_add(protocol_cut).unwrap();
+        accrue_senior_interest(pool, total - mortgage.outstanding_balance - reserve_cut - protocol_cut, current_time);
+        update_exchange_rate(pool);
+
+        mortgage.total_paid = mortgage.total_paid.checked_add(total).unwrap();
+        mortgage.outstanding_balance = 0;
+        mortgage.accrued_interest = 0;
+        mortgage.last_payment_date = current_time;
+        mortgage.status = MortgageStatus::Repaid;
+        mortgage.is_active = false;
+
+        msg!("Mortgage repaid in full: {} USDC (prepayment penalty: {})", total, penalty);
+        Ok(())
+    }
+
+    /// Repay a mortgage with a new loan from another pool, handing the collateral escrow to the new loan
+    pub fn refinance_mortgage(
+        ctx: Context<RefinanceMortgage>,
+        term_periods: u32,
+        payment_frequency: u64,
+        rate_type: RateType,
+    ) -> Result<()> {
+        let old_pool = &mut ctx.accounts.mortgage_pool;
+        let new_pool = &mut ctx.accounts.new_mortgage_pool;
+        let mortgage = &mut ctx.accounts.mortgage;
+        let new_mortgage = &mut ctx.accounts.new_mortgage;
+        let attestation = &ctx.accounts.credit_attestation;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(mortgage.status == MortgageStatus::Funded, MortgageError::MortgageNotFunded);
+        require_keys_eq!(mortgage.borrower, ctx.accounts.borrower.key(), MortgageError::Unauthorized);
+        require!(
+            can_underwrite(new_pool, ctx.accounts.underwriter.as_deref(), ctx.accounts.approver.key()),
+            MortgageError::Unauthorized
+        );
+        require!(new_pool.is_active, MortgageError::InactivePool);
+        require!(ctx.accounts.credit_bureau.is_active, MortgageError::InactiveCreditBureau);
+        require!(current_time < attestation.expires_at, MortgageError::AttestationExpired);
+        require!(attestation.credit_score >= new_pool.min_credit_score, MortgageError::InsufficientCreditScore);
+        require!(valid_loan_terms(term_periods, payment_frequency), MortgageError::InvalidLoanTerms);
+
+        accrue_borrow_index(old_pool, current_time);
+        accrue_borrow_index(new_pool, current_time);
+        unbook_loan_interest(old_pool, mortgage);
+
+        // The new loan covers the old payoff plus any prepayment penalty
+        let old_balance = mortgage.outstanding_balance;
+        let payoff = payoff_amount(mortgage, old_pool, current_time);
+        let penalty = prepayment_penalty(old_pool, mortgage, current_time);
+        let loan_amount = payoff.checked_add(penalty).unwrap();
+
+        require!(price_is_fresh(new_pool, current_time), MortgageError::StaleCollateralPrice);
+        let collateral_value = collateral_value(&ctx.accounts.property, mortgage.collateral_shares, new_pool);
+        require!(
+            loan_to_value(loan_amount, collateral_value) <= new_pool.max_ltv,
+            MortgageError::ExceedsMaxLTV
+        );
+        require!(new_pool.total_liquidity >= loan_amount, MortgageError::InsufficientLiquidity);
+
+        let interest = payoff.checked_sub(old_balance).unwrap();
+        let (reserve_cut, protocol_cut) = interest_fees(old_pool, interest);
+        let pool_amount = loan_amount - reserve_cut - protocol_cut;
+
+        let new_pool_seeds: &[&[&[u8]]] = &[&[
+            b"mortgage_pool",
+            new_pool.authority.as_ref(),
+            new_pool.pool_name.as_bytes(),
+            &[new_pool.bump],
+        ]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.new_pool_vault.to_account_info(),
+                    to: ctx.accounts.pool_vault.to_account_info(),
+                    authority: new_pool.to_account_info(),
+                },
+                new_pool_seeds,
+            ),
+            pool_amount,
+        )?;
+
+        if reserve_cut > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.new_pool_vault.to_account_info(),
+                        to: ctx.accounts.reserve_vault.to_account_info(),
+                        authority: new_pool.to_account_info(),
+                    },
+                    new_pool_seeds,
+                ),
+                reserve_cut,
+            )?;
+        }
+
+        if protocol_cut > 0 {
+            let treasury = ctx
+                .accounts
+                .protocol_treasury
+                .as_ref()
+                .ok_or(MortgageError::InvalidProtocolTreasury)?;
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.new_pool_vault.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: new_pool.to_account_info(),
+                    },
+                    new_pool_seeds,
+                ),
+                protocol_cut,
+            )?;
+        }
+
+        // Collateral stays in the same escrow; only its owner changes to the new mortgage
+        let loan_index = mortgage.loan_index.to_le_bytes();
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: mortgage.to_account_info(),
+                    account_or_mint: ctx.accounts.collateral_escrow.to_account_info(),
+                },
+                &[&[
+                    b"mortgage",
+                    mortgage.borrower.as_ref(),
+                    mortgage.pool.as_ref(),
+                    loan_index.as_ref(),
+                    &[mortgage.bump],
+                ]],
+            ),
+            AuthorityType::AccountOwner,
+            Some(new_mortgage.key()),
+        )?;
+
+        old_pool.total_liquidity = old_pool.total_liquidity.checked_add(pool_amount).unwrap();
+        old_pool.total_borrowed = old_pool.total_borrowed.saturating_sub(old_balance);
+        old_pool.total_reserves = old_pool.total_reserves.checked_add(reserve_cut).unwrap();
+        old_pool.total_protocol_fees = old_pool.total_protocol_fees.checked_add(protocol_cut).unwrap();
+        accrue_senior_interest(old_pool, loan_amount - old_balance - reserve_cut - protocol_cut, current_time);
+        update_exchange_rate(old_pool);
+
+        new_pool.total_liquidity = new_pool.total_liquidity.checked_sub(loan_amount).unwrap();
+        new_pool.total_borrowed = new_pool.total_borrowed.checked_add(loan_amount).unwrap();
+        update_exchange_rate(new_pool);
+
+        mortgage.total_paid = mortgage.total_paid.checked_add(loan_amount).unwrap();
+        mortgage.outstanding_balance = 0;
+        mortgage.accrued_interest = 0;
+        mortgage.last_payment_date = current_time;
+        mortgage.status = MortgageStatus::Refinanced;
+        mortgage.is_active = false;
+
+        let borrower_account = &mut ctx.accounts.borrower_account;
+        borrower_account.borrower = mortgage.borrower;
+        borrower_account.pool = new_pool.key();
+        borrower_account.bump = *ctx.bumps.get("borrower_account").unwrap();
+
+        new_mortgage.borrower = mortgage.borrower;
+        new_mortgage.pool = new_pool.key();
+        new_mortgage.loan_index = borrower_account.loan_count;
+        new_mortgage.property = mortgage.property;
+        new_mortgage.property_mint = mortgage.property_mint;
+        new_mortgage.collateral_escrow = mortgage.collateral_escrow;
+        new_mortgage.loan_amount = loan_amount;
+        new_mortgage.collateral_shares = mortgage.collateral_shares;
+        new_mortgage.collateral_value = collateral_value;
+        new_mortgage.current_ltv = loan_to_value(loan_amount, collateral_value);
+        new_mortgage.ltv_breached = false;
+        new_mortgage.last_valuation = current_time;
+        new_mortgage.rate_type = rate_type.clone();
+        new_mortgage.interest_rate = if rate_type == RateType::Variable {
+            current_borrow_rate(new_pool)
+        } else {
+            new_pool.interest_rate
+        };
+        new_mortgage.borrow_index_snapshot = if rate_type == RateType::Variable { new_pool.borrow_index } else { 0 };
+        new_mortgage.accrued_interest = 0;
+        new_mortgage.capitalized_interest = 0;
+        new_mortgage.outstanding_balance = loan_amount;
+        new_mortgage.credit_score = attestation.credit_score;
+        new_mortgage.credit_attestation = attestation.key();
+        new_mortgage.term_periods = term_periods;
+        new_mortgage.payment_frequency = payment_frequency;
+        new_mortgage.installments_paid = 0;
+        new_mortgage.installment_amount = amortized_installment(new_mortgage);
+        new_mortgage.next_due_date = current_time.checked_add(payment_frequency as i64).unwrap();
+        new_mortgage.missed_payments = 0;
+        new_mortgage.total_paid = 0;
+        new_mortgage.delinquency = DelinquencyStatus::Current;
+        new_mortgage.status = MortgageStatus::Funded;
+        new_mortgage.approved_by = Some(ctx.accounts.approver.key());
+        new_mortgage.approved_at = current_time;
+        new_mortgage.rejection_reason = None;
+        new_mortgage.is_active = true;
+        new_mortgage.start_date = current_time;
+        new_mortgage.last_payment_date = 0;
+        new_mortgage.bump = *ctx.bumps.get("new_mortgage").unwrap();
+
+        book_loan_interest(new_pool, new_mortgage);
+        update_exchange_rate(new_pool);
+
+        borrower_account.loan_count = borrower_account.loan_count.checked_add(1).unwrap();
+
+        msg!(
+            "Mortgage refinanced: {} USDC (prepayment penalty: {}), {} installments of {} USDC",
+            loan_amount,
+            penalty,
+            term_periods,
+            new_mortgage.installment_amount
+        );
+        Ok(())
+    }
+
+    /// Close a finished mortgage and its empty collateral escrow, returning rent to the borrower
+    pub fn close_mortgage(
+        ctx: Context<CloseMortgage>,
+    ) -> Result<()> {
+        let mortgage = &ctx.accounts.mortgage;
+
+        // A defaulted loan is finished once its foreclosure auction has been settled
+        let auction_settled = ctx.accounts.auction.as_ref().is_some_and(|auction| auction.is_settled);
+        require!(
+            mortgage.status == MortgageStatus::Repaid
+                || mortgage.status == MortgageStatus::Rejected
+                || mortgage.status == MortgageStatus::Cancelled
+                || mortgage.status == MortgageStatus::Refinanced
+                || (mortgage.status == MortgageStatus::Defaulted && auction_settled),
+            MortgageError::InvalidMortgageStatus
+        );
+
+        // A refinanced loan's escrow now belongs to the new mortgage
+        if mortgage.status != MortgageStatus::Refinanced {
+            let escrow = ctx
+                .accounts
+                .collateral_escrow
+                .as_ref()
+                .ok_or(MortgageError::InvalidCollateralEscrow)?;
+            require!(escrow.amount == 0, MortgageError::CollateralNotReleased);
+
+            let loan_index = mortgage.loan_index.to_le_bytes();
+            token::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: escrow.to_account_info(),
+                    destination: ctx.accounts.borrower.to_account_info(),
+                    authority: mortgage.to_account_info(),
+                },
+                &[&[
+                    b"mortgage",
+                    mortgage.borrower.as_ref(),
+                    mortgage.pool.as_ref(),
+                    loan_index.as_ref(),
+                    &[mortgage.bump],
+                ]],
+            ))?;
+        }
+
+        msg!("Mortgage {} closed", mortgage.loan_index);
+        Ok(())
+    }
+
+    /// Amount needed to pay the mortgage off in full at the given timestamp, including any prepayment penalty
+    pub fn get_payoff_amount(
+        ctx: Context<GetPayoffAmount>,
+        timestamp: i64,
+    ) -> Result<u64> {
+        let mortgage = &ctx.accounts.mortgage;
+        let pool = &ctx.accounts.mortgage_pool;
+
+        require!(mortgage.status == MortgageStatus::Funded, MortgageError::MortgageNotFunded);
+
+        let payoff = payoff_amount(mortgage, pool, timestamp);
+        let penalty = prepayment_penalty(pool, mortgage, timestamp);
+        let total = payoff.checked_add(penalty).unwrap();
+
+        msg!("Payoff amount at {}: {} USDC (prepayment penalty: {})", timestamp, total, penalty);
+        Ok(total)
+    }
+
+    /// Re-value mortgage collateral at the property's current share price
+    pub fn revalue_collateral(
+        ctx: Context<RevalueCollateral>,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.mortgage_pool;
+        let mortgage = &mut ctx.accounts.mortgage;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(mortgage.is_active, MortgageError::InactiveMortgage);
+        require!(price_is_fresh(pool, current_time), MortgageError::StaleCollateralPrice);
+
+        refresh_ltv(mortgage, &ctx.accounts.property, pool, current_time);
+
+        msg!(
+            "Collateral revalued at {} USDC, LTV {} bps{}",
+            mortgage.collateral_value,
+            mortgage.current_ltv,
+            if mortgage.ltv_breached { " (exceeds max LTV)" } else { "" }
+        );
+        Ok(())
+    }
+
+    /// Declare a mortgage in default after too many missed installments and open a foreclosure auction
+    pub fn declare_default(
+        ctx: Context<DeclareDefault>,
+    ) -> Result<()> {
+        let mortgage = &mut ctx.accounts.mortgage;
+        let pool = &mut ctx.accounts.mortgage_pool;
+        let auction = &mut ctx.accounts.auction;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(mortgage.status == MortgageStatus::Funded, MortgageError::MortgageNotFunded);
+
+        mortgage.missed_payments = installments_due(mortgage, current_time).saturating_sub(mortgage.installments_paid);
+        require!(mortgage.missed_payments >= DEFAULT_MISSED_PAYMENTS, MortgageError::NotInDefault);
+
+        accrue_borrow_index(pool, current_time);
+        let debt = payoff_amount(mortgage, pool, current_time);
+
+        // Write the principal off now so the junior tranche takes the loss before anyone can exit at the old price;
+        // auction proceeds are booked as a recovery
+        unbook_loan_interest(pool, mortgage);
+        pool.total_borrowed = pool.total_borrowed.saturating_sub(mortgage.outstanding_balance);
+        pool.uncovered_losses = pool.uncovered_losses.checked_add(mortgage.outstanding_balance).unwrap();
+        absorb_senior_losses(pool);
+        update_exchange_rate(pool);
+
+        mortgage.delinquency = DelinquencyStatus::Defaulted;
+        mortgage.status = MortgageStatus::Defaulted;
+        mortgage.is_active = false;
+
+        auction.mortgage = mortgage.key();
+        auction.pool = mortgage.pool;
+        auction.property_mint = mortgage.property_mint;
+        auction.collateral_shares = ctx.accounts.collateral_escrow.amount;
+        auction.debt = debt;
+        auction.bid_escrow = ctx.accounts.bid_escrow.key();
+        auction.highest_bid = 0;
+        auction.highest_bidder = None;
+        auction.ends_at = current_time.checked_add(AUCTION_DURATION).unwrap();
+        auction.rounds = 0;
+        auction.is_settled = false;
+        auction.created_at = current_time;
+        auction.bump = *ctx.bumps.get("auction").unwrap();
+
+        msg!(
+            "Mortgage defaulted after {} missed payments, auctioning {} shares against {} USDC debt",
+            mortgage.missed_payments,
+            auction.collateral_shares,
+            debt
+        );
+        Ok(())
+    }
+
+    /// Bid on foreclosed collateral; the previous highest bid is refunded
+    pub fn place_bid(
+        ctx: Context<PlaceBid>,
+        amount: u64,
+    ) -> Result<()> {
+        let auction = &mut ctx.accounts.auction;
+
+        require!(!auction.is_settled, MortgageError::AuctionSettled);
+        require!(Clock::get()?.unix_timestamp < auction.ends_at, MortgageError::AuctionEnded);
+        require!(amount > auction.highest_bid, MortgageError::BidTooLow);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.bidder_token_account.to_account_info(),
+                    to: ctx.accounts.bid_escrow.to_account_info(),
+                    authority: ctx.accounts.bidder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        if let Some(previous_bidder) = auction.highest_bidder {
+            let refund_account = ctx
+                .accounts
+                .previous_bidder_token_account
+                .as_ref()
+                .ok_or(MortgageError::MissingBidderAccount)?;
+            require_keys_eq!(refund_account.owner, previous_bidder, MortgageError::MissingBidderAccount);
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.bid_escrow.to_account_info(),
+                        to: refund_account.to_account_info(),
+                        authority: auction.to_account_info(),
+                    },
+                    &[&[b"auction", auction.mortgage.as_ref(), &[auction.bump]]],
+                ),
+                auction.highest_bid,
+            )?;
+        }
+
+        auction.highest_bid = amount;
+        auction.highest_bidder = Some(ctx.accounts.bidder.key());
+
+        msg!("Bid of {} USDC placed", amount);
+        Ok(())
+    }
+
+    /// Settle a finished auction: proceeds repay the pool first, any surplus goes to the borrower.
+    /// Collateral nobody bids on within MAX_AUCTION_ROUNDS goes to the pool authority.
+    pub fn settle_auction(
+        ctx: Context<SettleAuction>,
+    ) -> Result<()> {
+        let auction = &mut ctx.accounts.auction;
+        let mortgage = &mut ctx.accounts.mortgage;
+        let pool = &mut ctx.accounts.mortgage_pool;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(!auction.is_settled, MortgageError::AuctionSettled);
+        require!(current_time >= auction.ends_at, MortgageError::AuctionActive);
+
+        accrue_borrow_index(pool, current_time);
+
+        let winner = match auction.highest_bidder {
+            Some(winner) => winner,
+            None => {
+                // No bids: run the auction again, and after the last round hand the collateral to the pool
+                // authority, leaving the principal written off at default as an uncovered loss
+                auction.rounds = auction.rounds.checked_add(1).unwrap();
+                if auction.rounds < MAX_AUCTION_ROUNDS {
+                    auction.ends_at = current_time.checked_add(AUCTION_DURATION).unwrap();
+                    msg!("Auction received no bids, extended until {}", auction.ends_at);
+                    return Ok(());
+                }
+                msg!("Auction received no bids in {} rounds, collateral taken by the pool", auction.rounds);
+                pool.authority
+            }
+        };
+        require_keys_eq!(ctx.accounts.winner_share_account.owner, winner, MortgageError::Unauthorized);
+        require_keys_eq!(ctx.accounts.borrower_token_account.owner, mortgage.borrower, MortgageError::Unauthorized);
+
+        // Hand the collateral to the winning bidder
+        let loan_index = mortgage.loan_index.to_le_bytes();
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.collateral_escrow.to_account_info(),
+                    to: ctx.accounts.winner_share_account.to_account_info(),
+                    authority: mortgage.to_account_info(),
+                },
+                &[&[
+                    b"mortgage",
+                    mortgage.borrower.as_ref(),
+                    mortgage.pool.as_ref(),
+                    loan_index.as_ref(),
+                    &[mortgage.bump],
+                ]],
+            ),
+            ctx.accounts.collateral_escrow.amount,
+        )?;
+
+        let pool_proceeds = auction.highest_bid.min(auction.debt);
+        let surplus = auction.highest_bid.checked_sub(pool_proceeds).unwrap();
+        let auction_seeds: &[&[&[u8]]] = &[&[b"auction", auction.mortgage.as_ref(), &[auction.bump]]];
+
+        // Proceeds above the written-off principal are interest and pay the usual fees
+        let interest = pool_proceeds.saturating_sub(mortgage.outstanding_balance);
+        let (reserve_cut, protocol_cut) = interest_fees(pool, interest);
+        let pool_amount = pool_proceeds - reserve_cut - protocol_cut;
+
+        if pool_amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.bid_escrow.to_account_info(),
+                        to: ctx.accounts.pool_vault.to_account_info(),
+                        authority: auction.to_account_info(),
+                    },
+                    auction_seeds,
+                ),
+                pool_amount,
+            )?;
+        }
+
+        if reserve_cut > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.bid_escrow.to_account_info(),
+                        to: ctx.accounts.reserve_vault.to_account_info(),
+                        authority: auction.to_account_info(),
+                    },
+                    auction_seeds,
+                ),
+                reserve_cut,
+            )?;
+        }
+
+        if protocol_cut > 0 {
+            let treasury = ctx
+                .accounts
+                .protocol_treasury
+                .as_ref()
+                .ok_or(MortgageError::InvalidProtocolTreasury)?;
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.bid_escrow.to_account_info(),
+                        to: treasury.to_account_info(),
+                        authority: auction.to_account_info(),
+                    },
+                    auction_seeds,
+                ),
+                protocol_cut,
+            )?;
+        }
+
+        if surplus > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.bid_escrow.to_account_info(),
+                        to: ctx.accounts.borrower_token_account.to_account_info(),
+                        authority: auction.to_account_info(),
+                    },
+                    auction_seeds,
+                ),
+                surplus,
+            )?;
+        }
+
+        // The principal was written off at default; proceeds up to it are a recovery and any shortfall stays uncovered
+        let recovered = pool_proceeds.min(mortgage.outstanding_balance);
+        pool.total_liquidity = pool.total_liquidity.checked_add(pool_amount).unwrap();
+        pool.total_reserves = pool.total_reserves.checked_add(reserve_cut).unwrap();
+        pool.total_protocol_fees = pool.total_protocol_fees.checked_add(protocol_cut).unwrap();
+        recover_losses(pool, recovered);
+        accrue_senior_interest(pool, interest - reserve_cut - protocol_cut, current_time);
+        update_exchange_rate(pool);
+
+        mortgage.outstanding_balance = 0;
+        auction.is_settled = true;
+
+        msg!("Auction settled: {} USDC to pool, {} USDC surplus to borrower", pool_proceeds, surplus);
+        Ok(())
+    }
+}
+
+/// Restart the timelock so every scheduled setting waits the full period after the latest change
+fn restart_parameter_timelock(pool: &mut MortgagePool) -> Result<()> {
+    pool.pending_params_effective_at = Clock::get()?.unix_timestamp.checked_add(PARAMETER_TIMELOCK).unwrap();
+    Ok(())
+}
+
+fn clear_pending_parameters(pool: &mut MortgagePool) {
+    pool.pending_risk_params = None;
+    pool.pending_rate_model = None;
+    pool.pending_interest_policy = None;
+    pool.pending_reserve_config = None;
+    pool.pending_prepayment_tiers = None;
+    pool.pending_params_effective_at = 0;
+}
+
+/// Interest rate per payment period, scaled by AMORTIZATION_SCALE
+fn period_rate(mortgage: &Mortgage) -> u128 {
+    (mortgage.interest_rate as u128)
+        .checked_mul(mortgage.payment_frequency as u128)
+        .unwrap()
+        .checked_mul(AMORTIZATION_SCALE)
+        .unwrap()
+        .checked_div(10000 * SECONDS_PER_YEAR as u128)
+        .unwrap()
+}
+
+/// Principal plus carried unpaid interest, which earns interest like principal does
+fn interest_bearing_balance(mortgage: &Mortgage) -> u64 {
+    mortgage.outstanding_balance.checked_add(mortgage.accrued_interest).unwrap()
+}
+
+/// Interest accrued on the interest-bearing balance over one payment period
+fn period_interest(mortgage: &Mortgage) -> u64 {
+    (interest_bearing_balance(mortgage) as u128)
+        .checked_mul(period_rate(mortgage))
+        .unwrap()
+        .checked_div(AMORTIZATION_SCALE)
+        .unwrap() as u64
+}
+
+/// At most MAX_TERM_PERIODS installments spanning at most MAX_LOAN_DURATION
+fn valid_loan_terms(term_periods: u32, payment_frequency: u64) -> bool {
+    term_periods > 0
+        && term_periods <= MAX_TERM_PERIODS
+        && payment_frequency > 0
+        && (term_periods as u64)
+            .checked_mul(payment_frequency)
+            .is_some_and(|duration| duration <= MAX_LOAN_DURATION)
+}
+
+/// Fixed installment that repays the balance over the remaining term: P * r * (1+r)^n / ((1+r)^n - 1).
+/// Once (1+r)^n is large the installment is indistinguishable from interest only, so growth saturates there.
+fn amortized_installment(mortgage: &Mortgage) -> u64 {
+    let periods = mortgage.term_periods.saturating_sub(mortgage.installments_paid).max(1);
+    let rate = period_rate(mortgage);
+    let balance = mortgage.outstanding_balance as u128;
+
+    if rate == 0 {
+        return balance
+            .checked_add(periods as u128 - 1)
+            .unwrap()
+            .checked_div(periods as u128)
+            .unwrap() as u64;
+    }
+
+    let interest = balance.saturating_mul(rate) / AMORTIZATION_SCALE;
+
+    let mut growth = AMORTIZATION_SCALE;
+    for _ in 0..periods {
+        growth = match growth.checked_mul(AMORTIZATION_SCALE.saturating_add(rate)) {
+            Some(grown) if grown / AMORTIZATION_SCALE < AMORTIZATION_SCALE * AMORTIZATION_SCALE => {
+                grown / AMORTIZATION_SCALE
+            }
+            _ => return u64::try_from(interest).unwrap_or(u64::MAX),
+        };
+    }
+
+    // I * g / (g - 1), written as I + I / (g - 1) to keep the product small
+    let installment = interest.saturating_add(interest.saturating_mul(AMORTIZATION_SCALE) / (growth - AMORTIZATION_SCALE));
+    u64::try_from(installment).unwrap_or(u64::MAX)
+}
+
+/// Simple interest accrued on the interest-bearing balance since the last payment
+fn interest_since_last_payment(mortgage: &Mortgage, now: i64) -> u64 {
+    let since = if mortgage.last_payment_date == 0 {
+        mortgage.start_date
+    } else {
+        mortgage.last_payment_date
+    };
+    let elapsed = now.saturating_sub(since).max(0) as u128;
+
+    (interest_bearing_balance(mortgage) as u128)
+        .checked_mul(mortgage.interest_rate as u128)
+        .unwrap()
+        .checked_mul(elapsed)
+        .unwrap()
+        .checked_div(10000 * SECONDS_PER_YEAR as u128)
+        .unwrap() as u64
+}
+
+/// Reserve and protocol treasury shares of an interest payment
+fn interest_fees(pool: &MortgagePool, interest: u64) -> (u64, u64) {
+    let reserve_cut = (interest as u128)
+        .checked_mul(pool.reserve_factor_bps as u128)
+        .unwrap()
+        .checked_div(10000)
+        .unwrap() as u64;
+    let protocol_cut = (interest as u128)
+        .checked_mul(pool.protocol_fee_bps as u128)
+        .unwrap()
+        .checked_div(10000)
+        .unwrap() as u64;
+    (reserve_cut, protocol_cut)
+}
+
+/// Penalty on prepaid principal from the first schedule tier the loan's age falls under
+fn prepayment_penalty(pool: &MortgagePool, mortgage: &Mortgage, now: i64) -> u64 {
+    let loan_age = now.saturating_sub(mortgage.start_date);
+    match pool.prepayment_tiers.iter().find(|tier| loan_age < tier.max_loan_age) {
+        Some(tier) => (mortgage.outstanding_balance as u128)
+            .checked_mul(tier.penalty_bps as u128)
+            .unwrap()
+            .checked_div(10000)
+            .unwrap() as u64,
+        None => 0,
+    }
+}
+
+/// Outstanding principal plus carried and newly accrued interest
+fn payoff_amount(mortgage: &Mortgage, pool: &MortgagePool, now: i64) -> u64 {
+    mortgage
+        .outstanding_balance
+        .checked_add(mortgage.accrued_interest)
+        .unwrap()
+        .checked_add(accrued_interest(mortgage, pool, now))
+        .unwrap()
+}
+
+/// Interest accrued since the last payment, at the fixed rate or through the pool's borrow index
+fn accrued_interest(mortgage: &Mortgage, pool: &MortgagePool, now: i64) -> u64 {
+    match mortgage.rate_type {
+        RateType::Fixed => interest_since_last_payment(mortgage, now),
+        RateType::Variable => index_interest(mortgage, projected_borrow_index(pool, now)),
+    }
+}
+
+/// Interest a variable-rate balance has accrued as the borrow index moved past the loan's snapshot
+fn index_interest(mortgage: &Mortgage, borrow_index: u128) -> u64 {
+    if mortgage.borrow_index_snapshot == 0 {
+        return 0;
+    }
+    let balance = interest_bearing_balance(mortgage);
+    let grown = (balance as u128)
+        .checked_mul(borrow_index)
+        .unwrap()
+        .checked_div(mortgage.borrow_index_snapshot)
+        .unwrap() as u64;
+    grown.saturating_sub(balance)
+}
+
+/// Borrow rate in basis points for the pool's utilization, with a steeper slope past the kink
+fn current_borrow_rate(pool: &MortgagePool) -> u64 {
+    let total = pool_total_assets(pool);
+    if total == 0 {
+        return pool.base_rate;
+    }
+    let utilization = (pool.total_borrowed as u128)
+        .checked_mul(10000)
+        .unwrap()
+        .checked_div(total as u128)
+        .unwrap() as u64;
+
+    if utilization <= pool.kink_utilization {
+        pool.base_rate
+            .checked_add(pool.rate_slope.checked_mul(utilization).unwrap() / pool.kink_utilization)
+            .unwrap()
+    } else {
+        let excess = utilization - pool.kink_utilization;
+        pool.base_rate
+            .checked_add(pool.rate_slope)
+            .unwrap()
+            .checked_add(pool.jump_rate_slope.checked_mul(excess).unwrap() / (10000 - pool.kink_utilization))
+            .unwrap()
+    }
+}
+
+/// Borrow index as of `now`, compounding the current rate since the last update
+fn projected_borrow_index(pool: &MortgagePool, now: i64) -> u128 {
+    let elapsed = now.saturating_sub(pool.last_index_update).max(0) as u128;
+    let growth = pool
+        .borrow_index
+        .checked_mul(current_borrow_rate(pool) as u128)
+        .unwrap()
+        .checked_mul(elapsed)
+        .unwrap()
+        .checked_div(10000 * SECONDS_PER_YEAR as u128)
+        .unwrap();
+    pool.borrow_index.checked_add(growth).unwrap()
+}
+
+/// Start tracking a funded loan's unpaid interest in the pool's assets
+fn book_loan_interest(pool: &mut MortgagePool, mortgage: &Mortgage) {
+    pool.carried_interest = pool.carried_interest.checked_add(mortgage.accrued_interest).unwrap();
+    match mortgage.rate_type {
+        RateType::Fixed => {
+            let (weight, weighted_start) = fixed_rate_weight(mortgage);
+            pool.fixed_rate_weight = pool.fixed_rate_weight.checked_add(weight).unwrap();
+            pool.fixed_weighted_start = pool.fixed_weighted_start.checked_add(weighted_start).unwrap();
+        }
+        RateType::Variable => {
+            pool.variable_balance = pool.variable_balance.checked_add(interest_bearing_balance(mortgage)).unwrap();
+            pool.variable_scaled_balance = pool
+                .variable_scaled_balance
+                .checked_add(variable_scaled_balance(mortgage))
+                .unwrap();
+        }
+    }
+}
+
+/// Stop tracking a loan's unpaid interest; call before its balance, rate or payment date changes
+fn unbook_loan_interest(pool: &mut MortgagePool, mortgage: &Mortgage) {
+    pool.carried_interest = pool.carried_interest.saturating_sub(mortgage.accrued_interest);
+    match mortgage.rate_type {
+        RateType::Fixed => {
+            let (weight, weighted_start) = fixed_rate_weight(mortgage);
+            pool.fixed_rate_weight = pool.fixed_rate_weight.saturating_sub(weight);
+            pool.fixed_weighted_start = pool.fixed_weighted_start.saturating_sub(weighted_start);
+        }
+        RateType::Variable => {
+            pool.variable_balance = pool.variable_balance.saturating_sub(interest_bearing_balance(mortgage));
+            pool.variable_scaled_balance = pool
+                .variable_scaled_balance
+                .saturating_sub(variable_scaled_balance(mortgage));
+        }
+    }
+}
+
+/// A fixed-rate loan's balance times its rate, and that weight times when interest started accruing
+fn fixed_rate_weight(mortgage: &Mortgage) -> (u128, u128) {
+    let since = if mortgage.last_payment_date == 0 {
+        mortgage.start_date
+    } else {
+        mortgage.last_payment_date
+    };
+    let weight = (interest_bearing_balance(mortgage) as u128)
+        .checked_mul(mortgage.interest_rate as u128)
+        .unwrap();
+    (weight, weight.checked_mul(since.max(0) as u128).unwrap())
+}
+
+fn variable_scaled_balance(mortgage: &Mortgage) -> u128 {
+    if mortgage.borrow_index_snapshot == 0 {
+        return 0;
+    }
+    (interest_bearing_balance(mortgage) as u128)
+        .checked_mul(INDEX_SCALE)
+        .unwrap()
+        .checked_div(mortgage.borrow_index_snapshot)
+        .unwrap()
+}
+
+/// Interest funded loans owe the pool as of the last index update, net of reserve and protocol fees
+fn interest_receivable(pool: &MortgagePool) -> u64 {
+    let now = pool.last_index_update.max(0) as u128;
+    let fixed = now
+        .checked_mul(pool.fixed_rate_weight)
+        .unwrap()
+        .saturating_sub(pool.fixed_weighted_start)
+        .checked_div(10000 * SECONDS_PER_YEAR as u128)
+        .unwrap();
+    let variable = pool
+        .variable_scaled_balance
+        .checked_mul(pool.borrow_index)
+        .unwrap()
+        .checked_div(INDEX_SCALE)
+        .unwrap()
+        .saturating_sub(pool.variable_balance as u128);
+    let gross = fixed
+        .checked_add(variable)
+        .unwrap()
+        .checked_add(pool.carried_interest as u128)
+        .unwrap();
+
+    let fee_bps = pool.reserve_factor_bps as u128 + pool.protocol_fee_bps as u128;
+    (gross.checked_mul(10000 - fee_bps).unwrap() / 10000) as u64
+}
+
+fn accrue_borrow_index(pool: &mut MortgagePool, now: i64) {
+    pool.borrow_index = projected_borrow_index(pool, now);
+    pool.last_index_update = now;
+}
+
+/// Installments that have fallen due since funding
+fn installments_due(mortgage: &Mortgage, now: i64) -> u32 {
+    if mortgage.next_due_date == 0 || now <= mortgage.start_date {
+        return 0;
+    }
+    let elapsed = (now - mortgage.start_date) as u64;
+    (elapsed / mortgage.payment_frequency).min(mortgage.term_periods as u64) as u32
+}
+
+/// Value of property shares at the property's price per share
+fn collateral_value(property: &Property, shares: u64, pool: &MortgagePool) -> u64 {
+    if property.total_shares == 0 {
+        return 0;
+    }
+    // Property values are quoted in lamports; convert them to the pool's quote asset
+    (shares as u128)
+        .checked_mul(property.total_value as u128)
+        .unwrap()
+        .checked_div(property.total_shares as u128)
+        .unwrap()
+        .checked_mul(pool.sol_price as u128)
+        .unwrap()
+        .checked_div(LAMPORTS_PER_SOL as u128)
+        .unwrap()
+        .min(u64::MAX as u128) as u64
+}
+
+/// Whether the pool's SOL price was set recently enough to value collateral
+fn price_is_fresh(pool: &MortgagePool, now: i64) -> bool {
+    pool.sol_price > 0 && now.saturating_sub(pool.sol_price_updated_at) <= MAX_PRICE_AGE
+}
+
+/// Loan-to-value ratio in basis points
+fn loan_to_value(balance: u64, collateral_value: u64) -> u64 {
+    if collateral_value == 0 {
+        return if balance == 0 { 0 } else { u64::MAX };
+    }
+    (balance as u128)
+        .checked_mul(10000)
+        .unwrap()
+        .checked_div(collateral_value as u128)
+        .unwrap() as u64
+}
+
+fn refresh_ltv(mortgage: &mut Mortgage, property: &Property, pool: &MortgagePool, now: i64) {
+    mortgage.collateral_value = collateral_value(property, mortgage.collateral_shares, pool);
+    mortgage.current_ltv = loan_to_value(mortgage.outstanding_balance, mortgage.collateral_value);
+    mortgage.ltv_breached = mortgage.current_ltv > pool.max_ltv;
+    mortgage.last_valuation = now;
+}
+
+/// Pool authority, or an active underwriter registered on the pool
+fn can_underwrite(pool: &MortgagePool, underwriter: Option<&Underwriter>, signer: Pubkey) -> bool {
+    signer == pool.authority || matches!(underwriter, Some(u) if u.is_active && u.underwriter == signer)
+}
+
+/// Cash on hand plus principal lent out and the interest accrued on it
+fn pool_total_assets(pool: &MortgagePool) -> u64 {
+    pool.total_liquidity
+        .checked_add(pool.total_borrowed)
+        .unwrap()
+        .checked_add(interest_receivable(pool))
+        .unwrap()
+}
+
+/// Assets backing a tranche; the junior tranche holds whatever is left after the senior claim
+fn tranche_assets(pool: &MortgagePool, tranche: &Tranche) -> u64 {
+    let total = pool_total_assets(pool);
+    if !pool.tranches_enabled {
+        return match tranche {
+            Tranche::Senior => total,
+            Tranche::Junior => 0,
+        };
+    }
+    let senior = pool.senior_assets.min(total);
+    match tranche {
+        Tranche::Senior => senior,
+        Tranche::Junior => total - senior,
+    }
+}
+
+fn tranche_shares(pool: &MortgagePool, tranche: &Tranche) -> u64 {
+    match tranche {
+        Tranche::Senior => pool.total_shares,
+        Tranche::Junior => pool.junior_shares,
+    }
+}
+
+fn tranche_mint(pool: &MortgagePool, tranche: &Tranche) -> Pubkey {
+    match tranche {
+        Tranche::Senior => pool.share_mint,
+        Tranche::Junior => pool.junior_share_mint,
+    }
+}
+
+fn share_exchange_rate(assets: u64, shares: u64) -> u64 {
+    if shares == 0 {
+        return EXCHANGE_RATE_SCALE;
+    }
+    (assets as u128)
+        .checked_mul(EXCHANGE_RATE_SCALE as u128)
+        .unwrap()
+        .checked_div(shares as u128)
+        .unwrap() as u64
+}
+
+fn update_exchange_rate(pool: &mut MortgagePool) {
+    pool.exchange_rate = share_exchange_rate(tranche_assets(pool, &Tranche::Senior), pool.total_shares);
+    pool.junior_exchange_rate = share_exchange_rate(tranche_assets(pool, &Tranche::Junior), pool.junior_shares);
+}
+
+/// Accrue the senior tranche's target return and pay it first out of interest the pool just received
+fn accrue_senior_interest(pool: &mut MortgagePool, interest_received: u64, now: i64) {
+    if !pool.tranches_enabled {
+        return;
+    }
+    let elapsed = now.saturating_sub(pool.last_senior_accrual).max(0) as u128;
+    let accrued = (pool.senior_assets as u128)
+        .checked_mul(pool.senior_target_rate as u128)
+        .unwrap()
+        .checked_mul(elapsed)
+        .unwrap()
+        .checked_div(10000 * SECONDS_PER_YEAR as u128)
+        .unwrap() as u64;
+    pool.senior_interest_owed = pool.senior_interest_owed.checked_add(accrued).unwrap();
+    pool.last_senior_accrual = now;
+
+    let paid = interest_received.min(pool.senior_interest_owed);
+    pool.senior_assets = pool.senior_assets.checked_add(paid).unwrap();
+    pool.senior_interest_owed = pool.senior_interest_owed.checked_sub(paid).unwrap();
+}
+
+/// Book a recovery of written-off principal, restoring any senior write-down before the junior tranche
+fn recover_losses(pool: &mut MortgagePool, amount: u64) {
+    pool.uncovered_losses = pool.uncovered_losses.saturating_sub(amount);
+
+    let senior_recovery = amount.min(pool.senior_written_down);
+    pool.senior_assets = pool.senior_assets.checked_add(senior_recovery).unwrap();
+    pool.senior_written_down = pool.senior_written_down.checked_sub(senior_recovery).unwrap();
+}
+
+/// Write the senior claim down once losses have exhausted the junior tranche
+fn absorb_senior_losses(pool: &mut MortgagePool) {
+    let total = pool_total_assets(pool);
+    if pool.tranches_enabled && pool.senior_assets > total {
+        pool.senior_written_down = pool
+            .senior_written_down
+            .checked_add(pool.senior_assets - total)
+            .unwrap();
+        pool.senior_assets = total;
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(pool_name: String)]
+pub struct CreateMortgagePool<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MortgagePool::INIT_SPACE,
+        seeds = [b"mortgage_pool", authority.key().as_ref(), pool_name.as_bytes()],
+        bump
+    )]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = usdc_mint,
+        token::authority = mortgage_pool,
+        seeds = [b"pool_vault", mortgage_pool.key().as_ref()],
+        bump
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = usdc_mint,
+        token::authority = mortgage_pool,
+        seeds = [b"reserve", mortgage_pool.key().as_ref()],
+        bump
+    )]
+    pub reserve_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 6,
+        mint::authority = mortgage_pool,
+        seeds = [b"pool_shares", mortgage_pool.key().as_ref()],
+        bump
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyParameterUpdate<'info> {
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMortgagePool<'info> {
+    #[account(mut, has_one = authority @ MortgageError::Unauthorized, close = authority)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.reserve_vault @ MortgageError::InvalidReserveVault)]
+    pub reserve_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EnableTranches<'info> {
+    #[account(mut, has_one = authority @ MortgageError::Unauthorized)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 6,
+        mint::authority = mortgage_pool,
+        seeds = [b"junior_shares", mortgage_pool.key().as_ref()],
+        bump
+    )]
+    pub junior_share_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePoolSettings<'info> {
+    #[account(mut, has_one = authority @ MortgageError::Unauthorized)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CoverLosses<'info> {
+    #[account(mut, has_one = authority @ MortgageError::Unauthorized)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(mut, address = mortgage_pool.reserve_vault @ MortgageError::InvalidReserveVault)]
+    pub reserve_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositLiquidity<'info> {
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    /// Share mint of the tranche being deposited to or withdrawn from
+    #[account(mut)]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub depositor_share_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    pub depositor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLiquidity<'info> {
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    /// Share mint of the tranche being deposited to or withdrawn from
+    #[account(mut)]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub withdrawer_share_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub withdrawer_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    pub withdrawer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AddCreditBureau<'info> {
+    #[account(has_one = authority @ MortgageError::Unauthorized)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + CreditBureau::INIT_SPACE,
+        seeds = [b"credit_bureau", mortgage_pool.key().as_ref(), bureau_address.key().as_ref()],
+        bump
+    )]
+    pub credit_bureau: Account<'info, CreditBureau>,
+
+    /// CHECK: Credit bureau's signing wallet
+    pub bureau_address: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveCreditBureau<'info> {
+    #[account(has_one = authority @ MortgageError::Unauthorized)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(
+        mut,
+        seeds = [b"credit_bureau", mortgage_pool.key().as_ref(), credit_bureau.bureau.as_ref()],
+        bump = credit_bureau.bump
+    )]
+    pub credit_bureau: Account<'info, CreditBureau>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct IssueCreditAttestation<'info> {
+    #[account(
+        seeds = [b"credit_bureau", credit_bureau.pool.as_ref(), bureau.key().as_ref()],
+        bump = credit_bureau.bump
+    )]
+    pub credit_bureau: Account<'info, CreditBureau>,
+
+    #[account(
+        init_if_needed,
+        payer = bureau,
+        space = 8 + CreditAttestation::INIT_SPACE,
+        seeds = [b"credit_attestation", bureau.key().as_ref(), borrower.key().as_ref()],
+        bump
+    )]
+    pub credit_attestation: Account<'info, CreditAttestation>,
+
+    /// CHECK: Borrower the score is attested for
+    pub borrower: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub bureau: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestMortgage<'info> {
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        space = 8 + BorrowerAccount::INIT_SPACE,
+        seeds = [b"borrower", mortgage_pool.key().as_ref(), borrower.key().as_ref()],
+        bump
+    )]
+    pub borrower_account: Account<'info, BorrowerAccount>,
+
+    #[account(
+        init,
+        payer = borrower,
+        space = 8 + Mortgage::INIT_SPACE,
+        seeds = [
+            b"mortgage",
+            borrower.key().as_ref(),
+            mortgage_pool.key().as_ref(),
+            borrower_account.loan_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub mortgage: Account<'info, Mortgage>,
+
+    #[account(
+        seeds = [b"credit_bureau", mortgage_pool.key().as_ref(), credit_bureau.bureau.as_ref()],
+        bump = credit_bureau.bump
+    )]
+    pub credit_bureau: Account<'info, CreditBureau>,
+
+    #[account(
+        seeds = [b"credit_attestation", credit_bureau.bureau.as_ref(), borrower.key().as_ref()],
+        bump = credit_attestation.bump
+    )]
+    pub credit_attestation: Account<'info, CreditAttestation>,
+
+    pub property: Account<'info, Property>,
+
+    #[account(address = property.token_mint @ MortgageError::InvalidPropertyMint)]
+    pub property_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = borrower,
+        token::mint = property_mint,
+        token::authority = mortgage,
+        seeds = [b"collateral", mortgage.key().as_ref()],
+        bump
+    )]
+    pub collateral_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub borrower_share_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct AddUnderwriter<'info> {
+    #[account(has_one = authority @ MortgageError::Unauthorized)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Underwriter::INIT_SPACE,
+        seeds = [b"underwriter", mortgage_pool.key().as_ref(), underwriter_address.key().as_ref()],
+        bump
+    )]
+    pub underwriter: Account<'info, Underwriter>,
+
+    /// CHECK: Underwriter's wallet address
+    pub underwriter_address: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveUnderwriter<'info> {
+    #[account(has_one = authority @ MortgageError::Unauthorized)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(
+        mut,
+        seeds = [b"underwriter", mortgage_pool.key().as_ref(), underwriter.underwriter.as_ref()],
+        bump = underwriter.bump
+    )]
+    pub underwriter: Account<'info, Underwriter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCollateralPrice<'info> {
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    /// Underwriter role, required when the signer is not the pool authority
+    #[account(
+        seeds = [b"underwriter", mortgage_pool.key().as_ref(), signer.key().as_ref()],
+        bump = underwriter.bump
+    )]
+    pub underwriter: Option<Account<'info, Underwriter>>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveMortgage<'info> {
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
+
+    #[account(address = mortgage.property @ MortgageError::InvalidProperty)]
+    pub property: Account<'info, Property>,
+
+    /// Underwriter role, required when the approver is not the pool authority
+    #[account(
+        seeds = [b"underwriter", mortgage_pool.key().as_ref(), approver.key().as_ref()],
+        bump = underwriter.bump
+    )]
+    pub underwriter: Option<Account<'info, Underwriter>>,
+
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RejectMortgage<'info> {
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
+
+    #[account(mut, address = mortgage.collateral_escrow @ MortgageError::InvalidCollateralEscrow)]
+    pub collateral_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub borrower_share_account: Account<'info, TokenAccount>,
+
+    /// Underwriter role, required when the approver is not the pool authority
+    #[account(
+        seeds = [b"underwriter", mortgage_pool.key().as_ref(), approver.key().as_ref()],
+        bump = underwriter.bump
+    )]
+    pub underwriter: Option<Account<'info, Underwriter>>,
+
+    pub approver: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMortgage<'info> {
+    #[account(mut, has_one = borrower @ MortgageError::Unauthorized)]
+    pub mortgage: Account<'info, Mortgage>,
+
+    #[account(mut, address = mortgage.collateral_escrow @ MortgageError::InvalidCollateralEscrow)]
+    pub collateral_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, token::authority = borrower)]
+    pub borrower_share_account: Account<'info, TokenAccount>,
+
+    pub borrower: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FundMortgage<'info> {
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
+
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub borrower_token_account: Account<'info, TokenAccount>,
+
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct PayMortgage<'info> {
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
+
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(mut)]
+    pub borrower_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage.collateral_escrow @ MortgageError::InvalidCollateralEscrow)]
+    pub collateral_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub borrower_share_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.reserve_vault @ MortgageError::InvalidReserveVault)]
+    pub reserve_vault: Account<'info, TokenAccount>,
 
-    /// Approve and fund mortgage
-    pub fn approve_mortgage(
-        ctx: Context<ApproveMortgage>,
-    ) -> Result<()> {
-        let pool = &mut ctx.accounts.mortgage_pool;
-        let mortgage = &ctx.accounts.mortgage;
+    /// Protocol fee destination, required when the pool charges a protocol fee
+    #[account(mut, constraint = Some(protocol_treasury.key()) == mortgage_pool.protocol_treasury @ MortgageError::InvalidProtocolTreasury)]
+    pub protocol_treasury: Option<Account<'info, TokenAccount>>,
 
-        require!(mortgage.is_active, MortgageError::InactiveMortgage);
-        require!(pool.total_liquidity >= mortgage.loan_amount, MortgageError::InsufficientLiquidity);
+    #[account(address = mortgage.property @ MortgageError::InvalidProperty)]
+    pub property: Account<'info, Property>,
 
-        // Transfer loan to borrower
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.pool_token_account.to_account_info(),
-                    to: ctx.accounts.borrower_token_account.to_account_info(),
-                    authority: ctx.accounts.authority.to_account_info(),
-                },
-            ),
-            mortgage.loan_amount,
-        )?;
+    pub borrower: Signer<'info>,
 
-        pool.total_borrowed = pool.total_borrowed.checked_add(mortgage.loan_amount).unwrap();
-        pool.total_liquidity = pool.total_liquidity.checked_sub(mortgage.loan_amount).unwrap();
+    pub token_program: Program<'info, Token>,
+}
 
-        msg!("Mortgage approved and funded: {} USDC", mortgage.loan_amount);
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct RepayMortgageInFull<'info> {
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
 
-    /// Make mortgage payment
-    pub fn pay_mortgage(
-        ctx: Context<PayMortgage>,
-        payment_amount: u64,
-    ) -> Result<()> {
-        let mortgage = &mut ctx.accounts.mortgage;
-        let pool = &mut ctx.accounts.mortgage_pool;
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
 
-        require!(mortgage.is_active, MortgageError::InactiveMortgage);
-        require_keys_eq!(mortgage.borrower, ctx.accounts.borrower.key(), MortgageError::Unauthorized);
+    #[account(mut)]
+    pub borrower_token_account: Account<'info, TokenAccount>,
 
-        // Calculate interest
-        let current_time = Clock::get()?.unix_timestamp;
-        let time_elapsed = if mortgage.last_payment_date == 0 {
-            current_time - mortgage.start_date
-        } else {
-            current_time - mortgage.last_payment_date
-        };
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
 
-        let interest = (mortgage.outstanding_balance as u128)
-            .checked_mul(mortgage.interest_rate as u128)
-            .unwrap()
-            .checked_mul(time_elapsed as u128)
-            .unwrap()
-            .checked_div(10000 * 365 * 86400)
-            .unwrap() as u64;
+    #[account(mut, address = mortgage.collateral_escrow @ MortgageError::InvalidCollateralEscrow)]
+    pub collateral_escrow: Account<'info, TokenAccount>,
 
-        // Transfer payment to pool
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.borrower_token_account.to_account_info(),
-                    to: ctx.accounts.pool_token_account.to_account_info(),
-                    authority: ctx.accounts.borrower.to_account_info(),
-                },
-            ),
-            payment_amount,
-        )?;
+    #[account(mut)]
+    pub borrower_share_account: Account<'info, TokenAccount>,
 
-        // Update balances
-        let principal_payment = if payment_amount > interest {
-            payment_amount - interest
-        } else {
-            0
-        };
+    #[account(mut, address = mortgage_pool.reserve_vault @ MortgageError::InvalidReserveVault)]
+    pub reserve_vault: Account<'info, TokenAccount>,
 
-        mortgage.outstanding_balance = mortgage.outstanding_balance.saturating_sub(principal_payment);
-        mortgage.last_payment_date = current_time;
-        pool.total_liquidity = pool.total_liquidity.checked_add(payment_amount).unwrap();
-        pool.total_borrowed = pool.total_borrowed.saturating_sub(principal_payment);
+    /// Protocol fee destination, required when the pool charges a protocol fee
+    #[account(mut, constraint = Some(protocol_treasury.key()) == mortgage_pool.protocol_treasury @ MortgageError::InvalidProtocolTreasury)]
+    pub protocol_treasury: Option<Account<'info, TokenAccount>>,
 
-        if mortgage.outstanding_balance == 0 {
-            mortgage.is_active = false;
-        }
+    pub borrower: Signer<'info>,
 
-        msg!("Payment: {} USDC (Interest: {}, Principal: {})", payment_amount, interest, principal_payment);
-        Ok(())
-    }
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-#[instruction(pool_name: String)]
-pub struct CreateMortgagePool<'info> {
+pub struct RefinanceMortgage<'info> {
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
+
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.reserve_vault @ MortgageError::InvalidReserveVault)]
+    pub reserve_vault: Account<'info, TokenAccount>,
+
+    /// Protocol fee destination, required when the old pool charges a protocol fee
+    #[account(mut, constraint = Some(protocol_treasury.key()) == mortgage_pool.protocol_treasury @ MortgageError::InvalidProtocolTreasury)]
+    pub protocol_treasury: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = new_mortgage_pool.key() != mortgage_pool.key() @ MortgageError::SamePoolRefinance,
+        constraint = new_mortgage_pool.usdc_mint == mortgage_pool.usdc_mint @ MortgageError::InvalidPool
+    )]
+    pub new_mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        space = 8 + BorrowerAccount::INIT_SPACE,
+        seeds = [b"borrower", new_mortgage_pool.key().as_ref(), borrower.key().as_ref()],
+        bump
+    )]
+    pub borrower_account: Account<'info, BorrowerAccount>,
+
     #[account(
         init,
-        payer = authority,
-        space = 8 + MortgagePool::INIT_SPACE,
-        seeds = [b"mortgage_pool", authority.key().as_ref(), pool_name.as_bytes()],
+        payer = borrower,
+        space = 8 + Mortgage::INIT_SPACE,
+        seeds = [
+            b"mortgage",
+            borrower.key().as_ref(),
+            new_mortgage_pool.key().as_ref(),
+            borrower_account.loan_count.to_le_bytes().as_ref()
+        ],
         bump
     )]
-    pub mortgage_pool: Account<'info, MortgagePool>,
+    pub new_mortgage: Account<'info, Mortgage>,
+
+    #[account(mut, address = new_mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub new_pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"credit_bureau", new_mortgage_pool.key().as_ref(), credit_bureau.bureau.as_ref()],
+        bump = credit_bureau.bump
+    )]
+    pub credit_bureau: Account<'info, CreditBureau>,
+
+    #[account(
+        seeds = [b"credit_attestation", credit_bureau.bureau.as_ref(), borrower.key().as_ref()],
+        bump = credit_attestation.bump
+    )]
+    pub credit_attestation: Account<'info, CreditAttestation>,
+
+    #[account(address = mortgage.property @ MortgageError::InvalidProperty)]
+    pub property: Account<'info, Property>,
+
+    #[account(mut, address = mortgage.collateral_escrow @ MortgageError::InvalidCollateralEscrow)]
+    pub collateral_escrow: Account<'info, TokenAccount>,
+
+    /// Underwriter role on the new pool, required when the approver is not its authority
+    #[account(
+        seeds = [b"underwriter", new_mortgage_pool.key().as_ref(), approver.key().as_ref()],
+        bump = underwriter.bump
+    )]
+    pub underwriter: Option<Account<'info, Underwriter>>,
+
+    pub approver: Signer<'info>,
 
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub borrower: Signer<'info>,
 
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct DepositLiquidity<'info> {
-    #[account(mut)]
-    pub mortgage_pool: Account<'info, MortgagePool>,
+pub struct CloseMortgage<'info> {
+    #[account(mut, has_one = borrower @ MortgageError::Unauthorized, close = borrower)]
+    pub mortgage: Account<'info, Mortgage>,
 
-    #[account(mut)]
-    pub depositor_token_account: Account<'info, TokenAccount>,
+    /// Escrow to close alongside the mortgage; not needed once refinanced
+    #[account(mut, address = mortgage.collateral_escrow @ MortgageError::InvalidCollateralEscrow)]
+    pub collateral_escrow: Option<Account<'info, TokenAccount>>,
 
-    #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    /// Foreclosure auction, required to close a defaulted mortgage
+    #[account(seeds = [b"auction", mortgage.key().as_ref()], bump = auction.bump)]
+    pub auction: Option<Account<'info, ForeclosureAuction>>,
 
-    pub depositor: Signer<'info>,
+    #[account(mut)]
+    pub borrower: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct RequestMortgage<'info> {
+pub struct GetPayoffAmount<'info> {
+    #[account(constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
+
+    pub mortgage_pool: Account<'info, MortgagePool>,
+}
+
+#[derive(Accounts)]
+pub struct RevalueCollateral<'info> {
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
+
     pub mortgage_pool: Account<'info, MortgagePool>,
 
+    #[account(address = mortgage.property @ MortgageError::InvalidProperty)]
+    pub property: Account<'info, Property>,
+}
+
+#[derive(Accounts)]
+pub struct DeclareDefault<'info> {
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
+    pub mortgage: Account<'info, Mortgage>,
+
+    #[account(mut)]
+    pub mortgage_pool: Account<'info, MortgagePool>,
+
+    #[account(address = mortgage.collateral_escrow @ MortgageError::InvalidCollateralEscrow)]
+    pub collateral_escrow: Account<'info, TokenAccount>,
+
     #[account(
         init,
-        payer = borrower,
-        space = 8 + Mortgage::INIT_SPACE,
-        seeds = [b"mortgage", borrower.key().as_ref(), mortgage_pool.key().as_ref()],
+        payer = caller,
+        space = 8 + ForeclosureAuction::INIT_SPACE,
+        seeds = [b"auction", mortgage.key().as_ref()],
         bump
     )]
-    pub mortgage: Account<'info, Mortgage>,
+    pub auction: Account<'info, ForeclosureAuction>,
+
+    #[account(address = mortgage_pool.usdc_mint)]
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = caller,
+        token::mint = usdc_mint,
+        token::authority = auction,
+        seeds = [b"auction_bids", auction.key().as_ref()],
+        bump
+    )]
+    pub bid_escrow: Account<'info, TokenAccount>,
 
     #[account(mut)]
-    pub borrower: Signer<'info>,
+    pub caller: Signer<'info>,
 
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct ApproveMortgage<'info> {
+pub struct PlaceBid<'info> {
     #[account(mut)]
-    pub mortgage_pool: Account<'info, MortgagePool>,
+    pub auction: Account<'info, ForeclosureAuction>,
 
-    pub mortgage: Account<'info, Mortgage>,
+    #[account(mut, address = auction.bid_escrow @ MortgageError::InvalidBidEscrow)]
+    pub bid_escrow: Account<'info, TokenAccount>,
 
     #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub bidder_token_account: Account<'info, TokenAccount>,
 
+    /// Refund destination for the bid being outbid
     #[account(mut)]
-    pub borrower_token_account: Account<'info, TokenAccount>,
+    pub previous_bidder_token_account: Option<Account<'info, TokenAccount>>,
 
-    pub authority: Signer<'info>,
+    pub bidder: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct PayMortgage<'info> {
-    #[account(mut)]
+pub struct SettleAuction<'info> {
+    #[account(mut, has_one = mortgage @ MortgageError::InvalidAuction)]
+    pub auction: Account<'info, ForeclosureAuction>,
+
+    #[account(mut, constraint = mortgage.pool == mortgage_pool.key() @ MortgageError::InvalidPool)]
     pub mortgage: Account<'info, Mortgage>,
 
     #[account(mut)]
     pub mortgage_pool: Account<'info, MortgagePool>,
 
+    #[account(mut, address = auction.bid_escrow @ MortgageError::InvalidBidEscrow)]
+    pub bid_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage.collateral_escrow @ MortgageError::InvalidCollateralEscrow)]
+    pub collateral_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.pool_vault @ MortgageError::InvalidPoolVault)]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = mortgage_pool.reserve_vault @ MortgageError::InvalidReserveVault)]
+    pub reserve_vault: Account<'info, TokenAccount>,
+
+    /// Protocol fee destination, required when the pool charges a protocol fee
+    #[account(mut, constraint = Some(protocol_treasury.key()) == mortgage_pool.protocol_treasury @ MortgageError::InvalidProtocolTreasury)]
+    pub protocol_treasury: Option<Account<'info, TokenAccount>>,
+
+    /// Winning bidder's share account, or the pool authority's once the auction fails
     #[account(mut)]
-    pub borrower_token_account: Account<'info, TokenAccount>,
+    pub winner_share_account: Account<'info, TokenAccount>,
 
     #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub borrower_token_account: Account<'info, TokenAccount>,
 
-    pub borrower: Signer<'info>,
+    pub caller: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
 }
@@ -290,8 +2959,54 @@ pub struct MortgagePool {
     pub interest_rate: u64,
     pub max_ltv: u64,
     pub min_credit_score: u16,
+    pub sol_price: u64, // quote-asset base units per SOL, used to value lamport-priced shares
+    pub sol_price_updated_at: i64,
     pub total_liquidity: u64,
     pub total_borrowed: u64,
+    pub usdc_mint: Pubkey,
+    pub pool_vault: Pubkey,
+    pub share_mint: Pubkey, // senior tranche shares when tranches are enabled
+    pub total_shares: u64,
+    pub exchange_rate: u64, // pool assets per share, scaled by EXCHANGE_RATE_SCALE
+    pub base_rate: u64,
+    pub rate_slope: u64,
+    pub jump_rate_slope: u64,
+    pub kink_utilization: u64,
+    pub borrow_index: u128, // scaled by INDEX_SCALE
+    pub last_index_update: i64,
+    pub fixed_rate_weight: u128,    // sum of fixed-rate balances times their rates
+    pub fixed_weighted_start: u128, // the same weights times the time each loan last paid
+    pub variable_balance: u64,
+    pub variable_scaled_balance: u128, // variable-rate balances divided by their index snapshots
+    pub carried_interest: u64,         // unpaid interest carried by funded loans
+    pub interest_policy: InterestPolicy,
+    #[max_len(4)]
+    pub prepayment_tiers: Vec<PrepaymentTier>,
+    pub reserve_vault: Pubkey,
+    pub reserve_factor_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub protocol_treasury: Option<Pubkey>,
+    pub total_reserves: u64,
+    pub total_protocol_fees: u64,
+    pub uncovered_losses: u64,
+    pub tranches_enabled: bool,
+    pub junior_share_mint: Pubkey,
+    pub junior_shares: u64,
+    pub junior_exchange_rate: u64,
+    pub senior_assets: u64, // senior claim on pool assets; the junior tranche holds the remainder
+    pub senior_target_rate: u64,
+    pub min_subordination_bps: u64,
+    pub senior_interest_owed: u64,
+    pub senior_written_down: u64,
+    pub last_senior_accrual: i64,
+    pub pending_risk_params: Option<RiskParams>,
+    pub pending_rate_model: Option<RateModel>,
+    pub pending_interest_policy: Option<InterestPolicy>,
+    pub pending_reserve_config: Option<ReserveConfig>,
+    #[max_len(4)]
+    pub pending_prepayment_tiers: Option<Vec<PrepaymentTier>>,
+    pub pending_params_effective_at: i64, // 0 when no update is scheduled
+    pub is_winding_down: bool,
     pub is_active: bool,
     pub created_at: i64,
     pub bump: u8,
@@ -302,18 +3017,168 @@ pub struct MortgagePool {
 pub struct Mortgage {
     pub borrower: Pubkey,
     pub pool: Pubkey,
+    pub loan_index: u64,
+    pub property: Pubkey,
     pub property_mint: Pubkey,
+    pub collateral_escrow: Pubkey,
     pub loan_amount: u64,
     pub collateral_shares: u64,
+    pub collateral_value: u64,
+    pub current_ltv: u64, // basis points
+    pub ltv_breached: bool,
+    pub last_valuation: i64,
     pub interest_rate: u64,
     pub outstanding_balance: u64,
     pub credit_score: u16,
+    pub credit_attestation: Pubkey,
+    pub rate_type: RateType,
+    pub borrow_index_snapshot: u128,
+    pub accrued_interest: u64, // unpaid interest carried between installments
+    pub capitalized_interest: u64,
+    pub term_periods: u32,
+    pub payment_frequency: u64,
+    pub installment_amount: u64,
+    pub next_due_date: i64,
+    pub installments_paid: u32,
+    pub missed_payments: u32,
+    pub total_paid: u64,
+    pub delinquency: DelinquencyStatus,
+    pub status: MortgageStatus,
+    pub approved_by: Option<Pubkey>,
+    pub approved_at: i64,
+    #[max_len(200)]
+    pub rejection_reason: Option<String>,
     pub is_active: bool,
     pub start_date: i64,
     pub last_payment_date: i64,
     pub bump: u8,
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct BorrowerAccount {
+    pub borrower: Pubkey,
+    pub pool: Pubkey,
+    pub loan_count: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ForeclosureAuction {
+    pub mortgage: Pubkey,
+    pub pool: Pubkey,
+    pub property_mint: Pubkey,
+    pub collateral_shares: u64,
+    pub debt: u64,
+    pub bid_escrow: Pubkey,
+    pub highest_bid: u64,
+    pub highest_bidder: Option<Pubkey>,
+    pub ends_at: i64,
+    pub rounds: u8, // rounds that ended without a bid
+    pub is_settled: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Underwriter {
+    pub pool: Pubkey,
+    pub underwriter: Pubkey,
+    pub is_active: bool,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CreditBureau {
+    pub pool: Pubkey,
+    pub bureau: Pubkey,
+    #[max_len(50)]
+    pub bureau_name: String,
+    pub is_active: bool,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CreditAttestation {
+    pub bureau: Pubkey,
+    pub borrower: Pubkey,
+    pub credit_score: u16,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum MortgageStatus {
+    Requested,
+    Approved,
+    Funded,
+    Rejected,
+    Repaid,
+    Defaulted,
+    Refinanced,
+    Cancelled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PrepaymentTier {
+    pub max_loan_age: i64, // seconds since funding this tier applies to
+    pub penalty_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RiskParams {
+    pub interest_rate: u64,
+    pub max_ltv: u64,
+    pub min_credit_score: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RateModel {
+    pub base_rate: u64,
+    pub rate_slope: u64,
+    pub jump_rate_slope: u64,
+    pub kink_utilization: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ReserveConfig {
+    pub reserve_factor_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub protocol_treasury: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum Tranche {
+    Senior,
+    Junior,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum RateType {
+    Fixed,
+    Variable,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum InterestPolicy {
+    Accrue,
+    Capitalize,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum DelinquencyStatus {
+    Current,
+    Late,
+    Defaulted,
+}
+
 #[error_code]
 pub enum MortgageError {
     #[msg("Mortgage pool is not active")]
@@ -328,4 +3193,245 @@ pub enum MortgageError {
     InsufficientLiquidity,
     #[msg("Unauthorized access")]
     Unauthorized,
+    #[msg("Share amount must be greater than zero")]
+    ZeroShares,
+    #[msg("Token account is not the pool vault")]
+    InvalidPoolVault,
+    #[msg("Mortgage does not belong to this pool")]
+    InvalidPool,
+    #[msg("Property is not tokenized")]
+    PropertyNotTokenized,
+    #[msg("Mint does not match the property's share token")]
+    InvalidPropertyMint,
+    #[msg("Token account is not the mortgage collateral escrow")]
+    InvalidCollateralEscrow,
+    #[msg("Property does not match the mortgage collateral")]
+    InvalidProperty,
+    #[msg("Collateral price must be greater than zero")]
+    InvalidCollateralPrice,
+    #[msg("Collateral price has not been updated recently")]
+    StaleCollateralPrice,
+    #[msg("Loan term must be 1 to 480 periods spanning at most 40 years")]
+    InvalidLoanTerms,
+    #[msg("Mortgage has not been funded")]
+    MortgageNotFunded,
+    #[msg("Mortgage has not missed enough payments to default")]
+    NotInDefault,
+    #[msg("Auction has already been settled")]
+    AuctionSettled,
+    #[msg("Auction has ended")]
+    AuctionEnded,
+    #[msg("Auction is still running")]
+    AuctionActive,
+    #[msg("Bid must exceed the current highest bid")]
+    BidTooLow,
+    #[msg("Previous bidder's token account is required for the refund")]
+    MissingBidderAccount,
+    #[msg("Token account is not the auction bid escrow")]
+    InvalidBidEscrow,
+    #[msg("Auction does not belong to this mortgage")]
+    InvalidAuction,
+    #[msg("Mortgage is not in the required status")]
+    InvalidMortgageStatus,
+    #[msg("Reason is too long")]
+    ReasonTooLong,
+    #[msg("Name is too long")]
+    NameTooLong,
+    #[msg("Credit bureau is not active")]
+    InactiveCreditBureau,
+    #[msg("Credit score is out of range")]
+    InvalidCreditScore,
+    #[msg("Attestation validity must be greater than zero")]
+    InvalidAttestationExpiry,
+    #[msg("Credit attestation has expired")]
+    AttestationExpired,
+    #[msg("Kink utilization must be between 0 and 10000 bps")]
+    InvalidRateModel,
+    #[msg("Too many prepayment penalty tiers")]
+    TooManyPrepaymentTiers,
+    #[msg("Prepayment tiers must have increasing ages and penalties of at most 10000 bps")]
+    InvalidPrepaymentTier,
+    #[msg("Cannot refinance into the same pool")]
+    SamePoolRefinance,
+    #[msg("Collateral escrow still holds shares")]
+    CollateralNotReleased,
+    #[msg("Reserve factor and protocol fee exceed 10000 bps, or fee set without a treasury")]
+    InvalidReserveConfig,
+    #[msg("Token account is not the pool reserve vault")]
+    InvalidReserveVault,
+    #[msg("Token account is not the protocol treasury")]
+    InvalidProtocolTreasury,
+    #[msg("Amount exceeds uncovered losses")]
+    ExceedsUncoveredLosses,
+    #[msg("Insufficient reserves")]
+    InsufficientReserves,
+    #[msg("Pool does not have tranches")]
+    TranchesNotEnabled,
+    #[msg("Pool already has tranches")]
+    TranchesAlreadyEnabled,
+    #[msg("Tranches can only be enabled before the pool takes liquidity")]
+    PoolHasLiquidity,
+    #[msg("Share mint does not match the tranche")]
+    InvalidShareMint,
+    #[msg("Tranche has outstanding shares but no assets")]
+    TrancheInsolvent,
+    #[msg("Withdrawal would leave too little junior capital under the senior tranche")]
+    SubordinationBreached,
+    #[msg("Invalid pool parameters")]
+    InvalidPoolParameters,
+    #[msg("No parameter update is scheduled")]
+    NoPendingParameterUpdate,
+    #[msg("Parameter update is still timelocked")]
+    ParameterUpdateTimelocked,
+    #[msg("Pool is winding down")]
+    PoolWindingDown,
+    #[msg("Pool is not winding down")]
+    PoolNotWindingDown,
+    #[msg("Pool still has open loans")]
+    PoolHasOpenLoans,
+    #[msg("Pool shares are still outstanding")]
+    PoolHasOutstandingShares,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONTH: u64 = SECONDS_PER_YEAR / 12;
+
+    fn mortgage(outstanding_balance: u64, interest_rate: u64, term_periods: u32) -> Mortgage {
+        Mortgage {
+            borrower: Pubkey::default(),
+            pool: Pubkey::default(),
+            loan_index: 0,
+            property: Pubkey::default(),
+            property_mint: Pubkey::default(),
+            collateral_escrow: Pubkey::default(),
+            loan_amount: outstanding_balance,
+            collateral_shares: 0,
+            collateral_value: 0,
+            current_ltv: 0,
+            ltv_breached: false,
+            last_valuation: 0,
+            interest_rate,
+            outstanding_balance,
+            credit_score: 0,
+            credit_attestation: Pubkey::default(),
+            rate_type: RateType::Fixed,
+            borrow_index_snapshot: 0,
+            accrued_interest: 0,
+            capitalized_interest: 0,
+            term_periods,
+            payment_frequency: MONTH,
+            installment_amount: 0,
+            next_due_date: 0,
+            installments_paid: 0,
+            missed_payments: 0,
+            total_paid: 0,
+            delinquency: DelinquencyStatus::Current,
+            status: MortgageStatus::Funded,
+            approved_by: None,
+            approved_at: 0,
+            rejection_reason: None,
+            is_active: true,
+            start_date: 1_000,
+            last_payment_date: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn amortized_installment_without_interest_rounds_up() {
+        assert_eq!(amortized_installment(&mortgage(120_000, 0, 12)), 10_000);
+        assert_eq!(amortized_installment(&mortgage(100, 0, 3)), 34);
+    }
+
+    #[test]
+    fn amortized_installment_matches_annuity_formula() {
+        // 1% per month over a year: 100,000 * 0.01 * 1.01^12 / (1.01^12 - 1) = 8,884.88
+        let installment = amortized_installment(&mortgage(100_000, 1200, 12));
+        assert!((8_884..=8_885).contains(&installment), "installment {}", installment);
+    }
+
+    #[test]
+    fn amortized_installment_only_spreads_remaining_periods() {
+        let mut loan = mortgage(120_000, 0, 24);
+        loan.installments_paid = 12;
+        assert_eq!(amortized_installment(&loan), 10_000);
+
+        // Past the term the whole balance is due at once
+        loan.installments_paid = 30;
+        assert_eq!(amortized_installment(&loan), 120_000);
+    }
+
+    #[test]
+    fn amortized_installment_handles_max_term() {
+        let installment = amortized_installment(&mortgage(1_000_000, 1200, MAX_TERM_PERIODS));
+        // Interest alone is 10,000 a period, so the installment sits just above it
+        assert!(installment > 10_000 && installment < 10_100, "installment {}", installment);
+    }
+
+    #[test]
+    fn amortized_installment_saturates_instead_of_overflowing() {
+        // Yearly installments over 480 periods at 10% overflowed before saturation
+        let mut loan = mortgage(1_000_000, 1000, MAX_TERM_PERIODS);
+        loan.payment_frequency = SECONDS_PER_YEAR;
+        assert_eq!(amortized_installment(&loan), 100_000);
+
+        // A repriced variable loan at a very high rate pays interest only
+        let mut loan = mortgage(1_000_000, 50_000, 300);
+        loan.payment_frequency = MONTH;
+        let installment = amortized_installment(&loan);
+        assert!((416_666..=416_667).contains(&installment), "installment {}", installment);
+    }
+
+    #[test]
+    fn valid_loan_terms_bounds_duration() {
+        assert!(valid_loan_terms(MAX_TERM_PERIODS, MONTH));
+        assert!(!valid_loan_terms(MAX_TERM_PERIODS + 1, MONTH));
+        assert!(!valid_loan_terms(MAX_TERM_PERIODS, SECONDS_PER_YEAR));
+        assert!(valid_loan_terms(40, SECONDS_PER_YEAR));
+        assert!(!valid_loan_terms(0, MONTH));
+        assert!(!valid_loan_terms(12, 0));
+    }
+
+    #[test]
+    fn period_interest_charges_one_period_at_the_annual_rate() {
+        assert_eq!(period_interest(&mortgage(100_000, 1200, 12)), 1_000);
+        assert_eq!(period_interest(&mortgage(100_000, 0, 12)), 0);
+    }
+
+    #[test]
+    fn period_interest_compounds_carried_interest() {
+        let mut loan = mortgage(100_000, 1200, 12);
+        loan.accrued_interest = 1_000;
+        assert_eq!(period_interest(&loan), 1_010);
+    }
+
+    #[test]
+    fn interest_since_last_payment_accrues_from_start_then_last_payment() {
+        let mut loan = mortgage(1_000_000, 1000, 12);
+        let year_later = loan.start_date + SECONDS_PER_YEAR as i64;
+        assert_eq!(interest_since_last_payment(&loan, year_later), 100_000);
+
+        loan.last_payment_date = loan.start_date + (SECONDS_PER_YEAR / 2) as i64;
+        assert_eq!(interest_since_last_payment(&loan, year_later), 50_000);
+
+        // A timestamp before the last payment accrues nothing
+        assert_eq!(interest_since_last_payment(&loan, loan.start_date), 0);
+    }
+
+    #[test]
+    fn index_interest_follows_borrow_index_growth() {
+        let mut loan = mortgage(1_000_000, 0, 12);
+        assert_eq!(index_interest(&loan, INDEX_SCALE * 2), 0);
+
+        loan.rate_type = RateType::Variable;
+        loan.borrow_index_snapshot = INDEX_SCALE;
+        assert_eq!(index_interest(&loan, INDEX_SCALE * 105 / 100), 50_000);
+        assert_eq!(index_interest(&loan, INDEX_SCALE), 0);
+
+        loan.accrued_interest = 100_000;
+        assert_eq!(index_interest(&loan, INDEX_SCALE * 105 / 100), 55_000);
+    }
 }
@@ -103,6 +103,43 @@ pub mod property_tokenization {
         msg!("Bought {} shares for {} lamports", amount, transfer_amount);
         Ok(())
     }
+
+    /// Approve a property manager to act on behalf of the property authority
+    pub fn approve_property_manager(
+        ctx: Context<ApprovePropertyManager>,
+        fee_bps: u16, // management fee taken from rent collected
+    ) -> Result<()> {
+        let property = &ctx.accounts.property;
+        let property_manager = &mut ctx.accounts.property_manager;
+
+        require_keys_eq!(property.authority, ctx.accounts.authority.key(), PropertyError::Unauthorized);
+        require!(fee_bps <= 10000, PropertyError::InvalidFee);
+
+        property_manager.property = property.key();
+        property_manager.manager = ctx.accounts.manager.key();
+        property_manager.fee_bps = fee_bps;
+        property_manager.is_active = true;
+        property_manager.approved_at = Clock::get()?.unix_timestamp;
+        property_manager.bump = *ctx.bumps.get("property_manager").unwrap();
+
+        msg!("Property manager approved: {}", property_manager.manager);
+        Ok(())
+    }
+
+    /// Revoke a property manager's approval
+    pub fn revoke_property_manager(
+        ctx: Context<RevokePropertyManager>,
+    ) -> Result<()> {
+        let property = &ctx.accounts.property;
+        let property_manager = &mut ctx.accounts.property_manager;
+
+        require_keys_eq!(property.authority, ctx.accounts.authority.key(), PropertyError::Unauthorized);
+
+        property_manager.is_active = false;
+
+        msg!("Property manager revoked: {}", property_manager.manager);
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -172,6 +209,42 @@ pub struct BuyShares<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ApprovePropertyManager<'info> {
+    pub property: Account<'info, Property>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PropertyManager::INIT_SPACE,
+        seeds = [b"manager", property.key().as_ref(), manager.key().as_ref()],
+        bump
+    )]
+    pub property_manager: Account<'info, PropertyManager>,
+
+    /// CHECK: Manager's wallet address
+    pub manager: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokePropertyManager<'info> {
+    pub property: Account<'info, Property>,
+
+    #[account(
+        mut,
+        seeds = [b"manager", property.key().as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump
+    )]
+    pub property_manager: Account<'info, PropertyManager>,
+
+    pub authority: Signer<'info>,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Property {
@@ -192,6 +265,17 @@ pub struct Property {
     pub bump: u8,
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct PropertyManager {
+    pub property: Pubkey,
+    pub manager: Pubkey,
+    pub fee_bps: u16,
+    pub is_active: bool,
+    pub approved_at: i64,
+    pub bump: u8,
+}
+
 #[error_code]
 pub enum PropertyError {
     #[msg("Property is already tokenized")]
@@ -202,4 +286,6 @@ pub enum PropertyError {
     InsufficientShares,
     #[msg("Unauthorized access")]
     Unauthorized,
+    #[msg("Fee cannot exceed 100%")]
+    InvalidFee,
 }
@@ -1,57 +1,315 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Approve, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use property_tokenization::{Property, PropertyManager};
 
 declare_id!("rEnTRkNerjvB8bVMnJLJST6nYWB2gVkZa6zHcHcXVU4");
 
+pub const MAX_TENANTS: usize = 4;
+pub const MAX_ACCEPTED_MINTS: usize = 3;
+pub const RATE_SCALE: u64 = 1_000_000;
+pub const LATE_PAYMENT_GRACE_PERIOD: i64 = 5 * 86400;
+
 #[program]
 pub mod rental_payment {
     use super::*;
 
-    /// Create a rental agreement
+    /// Initialize a rental config naming the dispute arbiter; agreements choose their config at creation
+    pub fn initialize_rental_config(
+        ctx: Context<InitializeRentalConfig>,
+        arbiter: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.rental_config;
+
+        config.authority = ctx.accounts.authority.key();
+        config.arbiter = arbiter;
+        config.bump = *ctx.bumps.get("rental_config").unwrap();
+
+        msg!("Rental config initialized with arbiter: {}", arbiter);
+        Ok(())
+    }
+
+    /// Replace the dispute arbiter
+    pub fn set_arbiter(
+        ctx: Context<SetArbiter>,
+        arbiter: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.rental_config;
+
+        config.arbiter = arbiter;
+
+        msg!("Arbiter updated: {}", arbiter);
+        Ok(())
+    }
+
+    /// Create the occupancy registry for a property (by the property authority)
+    pub fn initialize_rental_registry(
+        ctx: Context<InitializeRentalRegistry>,
+        total_units: u32,
+    ) -> Result<()> {
+        let property = &ctx.accounts.property;
+        let registry = &mut ctx.accounts.rental_registry;
+
+        require_keys_eq!(property.authority, ctx.accounts.authority.key(), RentalError::NotPropertyAuthority);
+        require!(total_units > 0, RentalError::InvalidUnits);
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        registry.property = property.key();
+        registry.total_units = total_units;
+        registry.active_agreements = 0;
+        registry.total_agreements = 0;
+        registry.vacant_unit_seconds = 0;
+        registry.cumulative_rent_collected = 0;
+        registry.last_update = current_time;
+        registry.created_at = current_time;
+        registry.bump = *ctx.bumps.get("rental_registry").unwrap();
+
+        msg!("Rental registry created for property: {} ({} units)", property.name, total_units);
+        Ok(())
+    }
+
+    /// Change the number of rentable units on a property
+    pub fn update_rental_units(
+        ctx: Context<UpdateRentalUnits>,
+        total_units: u32,
+    ) -> Result<()> {
+        let property = &ctx.accounts.property;
+        let registry = &mut ctx.accounts.rental_registry;
+
+        require_keys_eq!(property.authority, ctx.accounts.authority.key(), RentalError::NotPropertyAuthority);
+        require!(
+            total_units > 0 && total_units >= registry.active_agreements,
+            RentalError::InvalidUnits
+        );
+
+        accrue_vacancy(registry, Clock::get()?.unix_timestamp);
+        registry.total_units = total_units;
+
+        msg!("Rental units updated: {}", total_units);
+        Ok(())
+    }
+
+    /// Create a rental agreement for a tokenized property
+    #[allow(clippy::too_many_arguments)]
     pub fn create_rental_agreement(
         ctx: Context<CreateRentalAgreement>,
-        property_id: Pubkey,
         rent_amount: u64,
         payment_frequency: u64, // in seconds (e.g., 30 days = 2592000)
+        security_deposit: u64,
+        notice_period: i64, // in seconds
+        tenant_splits: Vec<TenantSplit>, // first entry is the primary tenant
+        accepted_mints: Vec<AcceptedMint>, // alternative currencies at fixed rates
+        rent_mode: RentMode,
     ) -> Result<()> {
+        let property = &ctx.accounts.property;
         let rental = &mut ctx.accounts.rental_agreement;
 
+        require!(payment_frequency > 0, RentalError::InvalidPaymentFrequency);
+        require!(notice_period >= 0, RentalError::InvalidNoticePeriod);
+        require!(
+            !tenant_splits.is_empty() && tenant_splits.len() <= MAX_TENANTS,
+            RentalError::InvalidTenantSplits
+        );
+        require_keys_eq!(tenant_splits[0].tenant, ctx.accounts.tenant.key(), RentalError::InvalidTenantSplits);
+
+        let total_bps = tenant_splits
+            .iter()
+            .map(|split| split.share_bps as u64)
+            .sum::<u64>();
+        require!(total_bps == 10000, RentalError::InvalidTenantSplits);
+
+        for (i, split) in tenant_splits.iter().enumerate() {
+            require!(split.share_bps > 0, RentalError::InvalidTenantSplits);
+            require!(
+                tenant_splits[..i].iter().all(|other| other.tenant != split.tenant),
+                RentalError::InvalidTenantSplits
+            );
+        }
+
+        require!(accepted_mints.len() <= MAX_ACCEPTED_MINTS, RentalError::InvalidAcceptedMints);
+        for (i, accepted) in accepted_mints.iter().enumerate() {
+            require!(accepted.rate > 0, RentalError::InvalidAcceptedMints);
+            require!(accepted.mint != ctx.accounts.payment_mint.key(), RentalError::InvalidAcceptedMints);
+            require!(
+                accepted_mints[..i].iter().all(|other| other.mint != accepted.mint),
+                RentalError::InvalidAcceptedMints
+            );
+        }
+
+        // Either the landlord signs, or an approved manager signs on their behalf
+        match ctx.accounts.property_manager.as_ref() {
+            Some(property_manager) => {
+                require!(property_manager.is_active, RentalError::NotPropertyAuthority);
+                require!(
+                    ctx.accounts.authority.key() == ctx.accounts.landlord.key()
+                        || ctx.accounts.authority.key() == property_manager.manager,
+                    RentalError::Unauthorized
+                );
+
+                rental.manager = Some(property_manager.manager);
+                rental.management_fee_bps = property_manager.fee_bps;
+            }
+            None => {
+                require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.landlord.key(), RentalError::Unauthorized);
+
+                rental.manager = None;
+                rental.management_fee_bps = 0;
+            }
+        }
+
         rental.landlord = ctx.accounts.landlord.key();
         rental.tenant = ctx.accounts.tenant.key();
-        rental.property_id = property_id;
+        rental.tenants = tenant_splits
+            .iter()
+            .map(|split| TenantShare {
+                tenant: split.tenant,
+                share_bps: split.share_bps,
+                last_payment_date: 0,
+                total_payments: 0,
+                amount_paid: 0,
+                late_payments: 0,
+            })
+            .collect();
+        rental.guarantor = None;
+        rental.guarantee_limit = 0;
+        rental.guarantor_paid = 0;
+        rental.property_id = property.key();
+        rental.rental_config = ctx.accounts.rental_config.key();
+        rental.payment_mint = ctx.accounts.payment_mint.key();
+        rental.accepted_mints = accepted_mints;
+        rental.rent_mode = rent_mode;
+        rental.stream_started_at = 0;
+        rental.stream_stopped_at = 0;
+        rental.stream_deposited = 0;
+        rental.stream_withdrawn = 0;
+        rental.stream_accrued_before = 0;
         rental.rent_amount = rent_amount;
         rental.payment_frequency = payment_frequency;
+        rental.security_deposit = security_deposit;
+        rental.deposit_paid = false;
+        rental.notice_period = notice_period;
         rental.last_payment_date = 0;
         rental.total_payments = 0;
+        rental.rent_collected = 0;
         rental.is_active = true;
+        rental.status = RentalStatus::Active;
+        rental.termination_reason = None;
+        rental.notice_given_by = Pubkey::default();
+        rental.termination_date = 0;
+        rental.dispute_claim = None;
+        rental.landlord_award = 0;
+        rental.unpaid_arrears = 0;
+        rental.amendment_count = 0;
+        rental.pending_rent_amount = 0;
+        rental.pending_payment_frequency = 0;
+        rental.pending_terms_effective_at = 0;
         rental.created_at = Clock::get()?.unix_timestamp;
+        rental.schedule_start = rental.created_at;
+        rental.periods_before_schedule = 0;
+        rental.rent_due_before_schedule = 0;
         rental.bump = *ctx.bumps.get("rental_agreement").unwrap();
 
-        msg!("Rental agreement created for property: {}", property_id);
+        let registry = &mut ctx.accounts.rental_registry;
+        require!(registry.active_agreements < registry.total_units, RentalError::NoVacantUnits);
+
+        // Each lease on a property gets a fresh number so renewals get a new address
+        rental.lease_number = registry.total_agreements;
+
+        accrue_vacancy(registry, rental.created_at);
+        registry.active_agreements = registry.active_agreements.checked_add(1).unwrap();
+        registry.total_agreements = registry.total_agreements.checked_add(1).unwrap();
+
+        msg!("Rental agreement created for property: {}", property.name);
+        Ok(())
+    }
+
+    /// Lock the tenant's security deposit in the agreement escrow
+    pub fn pay_security_deposit(
+        ctx: Context<PaySecurityDeposit>,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+
+        require!(rental.status == RentalStatus::Active, RentalError::InactiveAgreement);
+        require_keys_eq!(rental.tenant, ctx.accounts.tenant.key(), RentalError::Unauthorized);
+        require!(!rental.deposit_paid, RentalError::DepositAlreadyPaid);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.tenant_token_account.to_account_info(),
+                    to: ctx.accounts.deposit_vault.to_account_info(),
+                    authority: ctx.accounts.tenant.to_account_info(),
+                },
+            ),
+            rental.security_deposit,
+        )?;
+
+        rental.deposit_paid = true;
+
+        msg!("Security deposit paid: {}", rental.security_deposit);
         Ok(())
     }
 
-    /// Pay rent using USDC
+    /// Pay the signing tenant's share of rent in an accepted mint and record a receipt
     pub fn pay_rent(
         ctx: Context<PayRent>,
+        period: u64, // the tenant's next unpaid period, starting at 0
     ) -> Result<()> {
         let rental = &mut ctx.accounts.rental_agreement;
 
         require!(rental.is_active, RentalError::InactiveAgreement);
-        require_keys_eq!(rental.tenant, ctx.accounts.tenant.key(), RentalError::Unauthorized);
+        require!(rental.rent_mode == RentMode::Periodic, RentalError::WrongRentMode);
 
         let current_time = Clock::get()?.unix_timestamp;
+        apply_due_amendment(rental, current_time);
+
+        let index = rental
+            .tenants
+            .iter()
+            .position(|share| share.tenant == ctx.accounts.tenant.key())
+            .ok_or(RentalError::Unauthorized)?;
+        let amount = tenant_rent_share(rental, index);
+
+        // Rent is denominated in the agreed mint and converted if paid in another
+        let mint = ctx.accounts.tenant_token_account.mint;
+        require_keys_eq!(ctx.accounts.tenant_token_account.owner, ctx.accounts.tenant.key(), RentalError::InvalidTokenAccount);
+        require_keys_eq!(ctx.accounts.landlord_token_account.owner, rental.landlord, RentalError::InvalidTokenAccount);
+        require_keys_eq!(ctx.accounts.landlord_token_account.mint, mint, RentalError::InvalidTokenAccount);
+
+        let amount_paid = convert_rent(rental, &mint, amount)?;
+        let management_fee = management_fee(rental, ctx.accounts.property_manager.as_deref(), amount_paid);
+
+        require!(period == rental.tenants[index].total_payments, RentalError::InvalidPeriod);
 
         // Check if payment is due
-        if rental.last_payment_date != 0 {
-            let time_since_last_payment = current_time - rental.last_payment_date;
-            require!(
-                time_since_last_payment as u64 >= rental.payment_frequency,
-                RentalError::PaymentNotDue
-            );
+        let due_date = period_due_date(rental, period);
+        require!(current_time >= due_date, RentalError::PaymentNotDue);
+
+        // Split the management fee off to the manager
+        if management_fee > 0 {
+            let manager_token_account = ctx
+                .accounts
+                .manager_token_account
+                .as_ref()
+                .ok_or(RentalError::InvalidTokenAccount)?;
+            require!(rental.manager == Some(manager_token_account.owner), RentalError::InvalidTokenAccount);
+            require_keys_eq!(manager_token_account.mint, mint, RentalError::InvalidTokenAccount);
+
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.tenant_token_account.to_account_info(),
+                        to: manager_token_account.to_account_info(),
+                        authority: ctx.accounts.tenant.to_account_info(),
+                    },
+                ),
+                management_fee,
+            )?;
         }
 
-        // Transfer USDC from tenant to landlord
+        // Transfer rent from tenant to landlord
         token::transfer(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
@@ -61,138 +319,1651 @@ pub mod rental_payment {
                     authority: ctx.accounts.tenant.to_account_info(),
                 },
             ),
-            rental.rent_amount,
+            amount_paid.checked_sub(management_fee).unwrap(),
         )?;
 
+        let is_late = current_time > due_date.checked_add(LATE_PAYMENT_GRACE_PERIOD).unwrap();
+
+        let share = &mut rental.tenants[index];
+        share.last_payment_date = current_time;
+        share.total_payments = share.total_payments.checked_add(1).unwrap();
+        share.amount_paid = share.amount_paid.checked_add(amount).unwrap();
+        if is_late {
+            share.late_payments = share.late_payments.checked_add(1).unwrap();
+        }
+
+        // A period counts as paid once every co-tenant has paid their share
+        rental.total_payments = rental.tenants.iter().map(|share| share.total_payments).min().unwrap();
         rental.last_payment_date = current_time;
-        rental.total_payments = rental.total_payments.checked_add(1).unwrap();
+        rental.rent_collected = rental.rent_collected.checked_add(amount).unwrap();
+
+        let registry = &mut ctx.accounts.rental_registry;
+        registry.cumulative_rent_collected = registry.cumulative_rent_collected.checked_add(amount).unwrap();
+
+        let receipt = &mut ctx.accounts.rent_receipt;
+        receipt.rental_agreement = rental.key();
+        receipt.tenant = ctx.accounts.tenant.key();
+        receipt.period = period;
+        receipt.amount = amount;
+        receipt.mint = mint;
+        receipt.amount_paid = amount_paid;
+        receipt.due_date = due_date;
+        receipt.paid_at = current_time;
+        receipt.is_late = is_late;
+        receipt.bump = *ctx.bumps.get("rent_receipt").unwrap();
+
+        emit!(RentPaid {
+            rental_agreement: receipt.rental_agreement,
+            tenant: receipt.tenant,
+            period,
+            amount,
+            mint,
+            amount_paid,
+            paid_at: current_time,
+            is_late,
+        });
 
-        msg!("Rent paid: {} USDC", rental.rent_amount);
+        msg!("Rent paid: {} in {} (management fee: {})", amount_paid, mint, management_fee);
         Ok(())
     }
 
-    /// Terminate rental agreement
-    pub fn terminate_rental(
-        ctx: Context<TerminateRental>,
+    /// Deposit or top up the streaming rent escrow; the first deposit starts the stream.
+    /// Top-ups remain open during the notice period so the tenant can cover it.
+    pub fn fund_stream(
+        ctx: Context<FundStream>,
+        amount: u64,
     ) -> Result<()> {
         let rental = &mut ctx.accounts.rental_agreement;
 
         require!(rental.is_active, RentalError::InactiveAgreement);
+        require!(rental.rent_mode == RentMode::Streaming, RentalError::WrongRentMode);
+        require_keys_eq!(rental.tenant, ctx.accounts.tenant.key(), RentalError::Unauthorized);
+        require!(rental.stream_stopped_at == 0, RentalError::StreamStopped);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.tenant_token_account.to_account_info(),
+                    to: ctx.accounts.stream_vault.to_account_info(),
+                    authority: ctx.accounts.tenant.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        if rental.stream_started_at == 0 {
+            rental.stream_started_at = current_time;
+        }
+        rental.stream_deposited = rental.stream_deposited.checked_add(amount).unwrap();
+
+        msg!("Stream funded with {} (total deposited: {})", amount, rental.stream_deposited);
+        Ok(())
+    }
+
+    /// Withdraw rent accrued so far on a streaming agreement
+    pub fn withdraw_streamed_rent(
+        ctx: Context<WithdrawStreamedRent>,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+
+        require!(rental.rent_mode == RentMode::Streaming, RentalError::WrongRentMode);
         require!(
-            rental.landlord == ctx.accounts.authority.key() || rental.tenant == ctx.accounts.authority.key(),
+            is_landlord_side(rental, ctx.accounts.property_manager.as_deref(), &ctx.accounts.authority.key()),
             RentalError::Unauthorized
         );
 
-        rental.is_active = false;
+        let current_time = Clock::get()?.unix_timestamp;
+        apply_due_amendment(rental, current_time);
+
+        let accrued = streamed_rent(rental, current_time).min(rental.stream_deposited);
+        let amount = accrued.checked_sub(rental.stream_withdrawn).unwrap();
+        require!(amount > 0, RentalError::NothingToWithdraw);
+
+        let management_fee = pay_out_stream(
+            rental,
+            &ctx.accounts.token_program,
+            &ctx.accounts.stream_vault,
+            &ctx.accounts.landlord_token_account,
+            ctx.accounts.manager_token_account.as_ref(),
+            ctx.accounts.property_manager.as_deref(),
+            amount,
+        )?;
+
+        rental.stream_withdrawn = accrued;
+
+        let registry = &mut ctx.accounts.rental_registry;
+        registry.cumulative_rent_collected = registry.cumulative_rent_collected.checked_add(amount).unwrap();
 
-        msg!("Rental agreement terminated");
+        msg!("Withdrew {} streamed rent (management fee: {})", amount, management_fee);
         Ok(())
     }
 
-    /// Distribute rental income to property token holders
-    pub fn distribute_rental_income(
-        ctx: Context<DistributeIncome>,
-        amount: u64,
+    /// Stop the stream, paying out accrued rent and refunding the unaccrued remainder.
+    /// Either side may stop it once the notice period has elapsed; rent accrues until then.
+    pub fn stop_stream(
+        ctx: Context<StopStream>,
     ) -> Result<()> {
-        let rental = &ctx.accounts.rental_agreement;
+        let rental = &mut ctx.accounts.rental_agreement;
+        let authority = ctx.accounts.authority.key();
+
+        require!(rental.rent_mode == RentMode::Streaming, RentalError::WrongRentMode);
+        require!(rental.stream_started_at != 0, RentalError::StreamNotStarted);
+        require!(rental.stream_stopped_at == 0, RentalError::StreamStopped);
+        require_keys_eq!(ctx.accounts.tenant_token_account.owner, rental.tenant, RentalError::InvalidTokenAccount);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            authority == rental.tenant
+                || is_landlord_side(rental, ctx.accounts.property_manager.as_deref(), &authority),
+            RentalError::Unauthorized
+        );
+        require!(
+            rental.termination_date != 0 && current_time >= rental.termination_date,
+            RentalError::NoticePeriodNotElapsed
+        );
+
+        apply_due_amendment(rental, current_time);
+        rental.stream_stopped_at = current_time;
+
+        let accrued = streamed_rent(rental, current_time).min(rental.stream_deposited);
+        let landlord_amount = accrued.checked_sub(rental.stream_withdrawn).unwrap();
+        let refund = rental.stream_deposited.checked_sub(accrued).unwrap();
+
+        if landlord_amount > 0 {
+            pay_out_stream(
+                rental,
+                &ctx.accounts.token_program,
+                &ctx.accounts.stream_vault,
+                &ctx.accounts.landlord_token_account,
+                ctx.accounts.manager_token_account.as_ref(),
+                ctx.accounts.property_manager.as_deref(),
+                landlord_amount,
+            )?;
+        }
+
+        let lease_number = rental.lease_number.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"rental",
+            rental.landlord.as_ref(),
+            rental.tenant.as_ref(),
+            rental.property_id.as_ref(),
+            lease_number.as_ref(),
+            &[rental.bump],
+        ]];
+
+        if refund > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.stream_vault.to_account_info(),
+                        to: ctx.accounts.tenant_token_account.to_account_info(),
+                        authority: rental.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                refund,
+            )?;
+        }
+
+        // Return the escrow's rent to the tenant who funded it
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.stream_vault.to_account_info(),
+                destination: ctx.accounts.tenant.to_account_info(),
+                authority: rental.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        rental.stream_withdrawn = accrued;
+
+        let registry = &mut ctx.accounts.rental_registry;
+        registry.cumulative_rent_collected = registry.cumulative_rent_collected.checked_add(landlord_amount).unwrap();
+
+        msg!("Stream stopped: {} to landlord, {} refunded", landlord_amount, refund);
+        Ok(())
+    }
+
+    /// Remove the property manager from an agreement (by the landlord)
+    pub fn remove_rental_manager(
+        ctx: Context<RemoveRentalManager>,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+
+        require_keys_eq!(rental.landlord, ctx.accounts.landlord.key(), RentalError::Unauthorized);
+
+        rental.manager = None;
+        rental.management_fee_bps = 0;
+
+        msg!("Property manager removed from rental agreement");
+        Ok(())
+    }
+
+    /// Register a guarantor who co-signs the agreement and pre-approves a guarantee limit
+    pub fn add_guarantor(
+        ctx: Context<AddGuarantor>,
+        guarantee_limit: u64,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
 
         require!(rental.is_active, RentalError::InactiveAgreement);
+        require_keys_eq!(rental.tenant, ctx.accounts.tenant.key(), RentalError::Unauthorized);
+        require!(rental.guarantor.is_none(), RentalError::GuarantorAlreadySet);
+        require_keys_eq!(ctx.accounts.guarantor_token_account.owner, ctx.accounts.guarantor.key(), RentalError::InvalidTokenAccount);
+        require_keys_eq!(ctx.accounts.guarantor_token_account.mint, rental.payment_mint, RentalError::InvalidTokenAccount);
+
+        // The agreement PDA becomes the delegate that can draw on the guarantee
+        token::approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.guarantor_token_account.to_account_info(),
+                    delegate: rental.to_account_info(),
+                    authority: ctx.accounts.guarantor.to_account_info(),
+                },
+            ),
+            guarantee_limit,
+        )?;
+
+        rental.guarantor = Some(ctx.accounts.guarantor.key());
+        rental.guarantee_limit = guarantee_limit;
+        rental.guarantor_paid = 0;
+
+        msg!("Guarantor {} added with limit {}", ctx.accounts.guarantor.key(), guarantee_limit);
+        Ok(())
+    }
+
+    /// Charge the guarantor for rent the tenants are in arrears on; on a live periodic
+    /// agreement the claim pays off whole overdue tenant periods and any remainder is not charged
+    pub fn claim_from_guarantor(
+        ctx: Context<ClaimFromGuarantor>,
+        amount: u64,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+
         require_keys_eq!(rental.landlord, ctx.accounts.landlord.key(), RentalError::Unauthorized);
+        require!(
+            rental.guarantor == Some(ctx.accounts.guarantor_token_account.owner),
+            RentalError::InvalidTokenAccount
+        );
+        require_keys_eq!(ctx.accounts.landlord_token_account.owner, rental.landlord, RentalError::InvalidTokenAccount);
+        require_keys_eq!(ctx.accounts.landlord_token_account.mint, rental.payment_mint, RentalError::InvalidTokenAccount);
 
-        // Transfer USDC from landlord to token holder
+        // Once notice is given, nothing is owed for the period starting on the termination date
+        let current_time = Clock::get()?.unix_timestamp;
+        let until = if rental.termination_date != 0 {
+            current_time.min(rental.termination_date - 1)
+        } else {
+            current_time
+        };
+        let arrears = if rental.status == RentalStatus::Terminated {
+            rental.unpaid_arrears
+        } else {
+            apply_due_amendment(rental, current_time);
+            rent_arrears(rental, until)
+        };
+
+        require!(amount > 0 && amount <= arrears, RentalError::ClaimExceedsArrears);
+
+        // Advance the delinquent tenants' schedules so the same periods can't be paid twice
+        let amount = if rental.status != RentalStatus::Terminated && rental.rent_mode == RentMode::Periodic {
+            let mut remaining = amount;
+            for index in 0..rental.tenants.len() {
+                let share_amount = tenant_rent_share(rental, index);
+                while remaining >= share_amount
+                    && period_due_date(rental, rental.tenants[index].total_payments) <= until
+                {
+                    let share = &mut rental.tenants[index];
+                    share.total_payments = share.total_payments.checked_add(1).unwrap();
+                    share.amount_paid = share.amount_paid.checked_add(share_amount).unwrap();
+                    remaining -= share_amount;
+                }
+            }
+            rental.total_payments = rental.tenants.iter().map(|share| share.total_payments).min().unwrap();
+
+            amount.checked_sub(remaining).unwrap()
+        } else {
+            amount
+        };
+        require!(amount > 0, RentalError::ClaimExceedsArrears);
+        require!(
+            rental.guarantor_paid.checked_add(amount).unwrap() <= rental.guarantee_limit,
+            RentalError::ClaimExceedsGuarantee
+        );
+
+        let lease_number = rental.lease_number.to_le_bytes();
         token::transfer(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
-                    from: ctx.accounts.landlord_token_account.to_account_info(),
-                    to: ctx.accounts.holder_token_account.to_account_info(),
-                    authority: ctx.accounts.landlord.to_account_info(),
+                    from: ctx.accounts.guarantor_token_account.to_account_info(),
+                    to: ctx.accounts.landlord_token_account.to_account_info(),
+                    authority: rental.to_account_info(),
                 },
+                &[&[
+                    b"rental",
+                    rental.landlord.as_ref(),
+                    rental.tenant.as_ref(),
+                    rental.property_id.as_ref(),
+                    lease_number.as_ref(),
+                    &[rental.bump],
+                ]],
             ),
             amount,
         )?;
 
-        msg!("Distributed {} USDC to token holder", amount);
+        rental.guarantor_paid = rental.guarantor_paid.checked_add(amount).unwrap();
+        if rental.status == RentalStatus::Terminated {
+            rental.unpaid_arrears = rental.unpaid_arrears.checked_sub(amount).unwrap();
+        } else {
+            rental.rent_collected = rental.rent_collected.checked_add(amount).unwrap();
+        }
+
+        let registry = &mut ctx.accounts.rental_registry;
+        registry.cumulative_rent_collected = registry.cumulative_rent_collected.checked_add(amount).unwrap();
+
+        msg!("Claimed {} USDC from guarantor", amount);
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(property_id: Pubkey)]
-pub struct CreateRentalAgreement<'info> {
-    #[account(
-        init,
-        payer = landlord,
-        space = 8 + RentalAgreement::INIT_SPACE,
-        seeds = [b"rental", landlord.key().as_ref(), tenant.key().as_ref(), property_id.as_ref()],
-        bump
-    )]
-    pub rental_agreement: Account<'info, RentalAgreement>,
+    /// Propose new rent terms; the counterparty must accept them
+    pub fn propose_amendment(
+        ctx: Context<ProposeAmendment>,
+        new_rent_amount: u64,
+        new_payment_frequency: u64, // in seconds
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+        let amendment = &mut ctx.accounts.amendment;
 
-    #[account(mut)]
-    pub landlord: Signer<'info>,
+        require!(rental.status == RentalStatus::Active, RentalError::InactiveAgreement);
+        require!(
+            is_party(rental, ctx.accounts.property_manager.as_deref(), &ctx.accounts.proposer.key()),
+            RentalError::Unauthorized
+        );
+        require!(new_payment_frequency > 0, RentalError::InvalidPaymentFrequency);
 
-    /// CHECK: Tenant address
-    pub tenant: AccountInfo<'info>,
+        amendment.rental_agreement = rental.key();
+        amendment.amendment_number = rental.amendment_count;
+        amendment.proposed_by = ctx.accounts.proposer.key();
+        amendment.previous_rent_amount = rental.rent_amount;
+        amendment.previous_payment_frequency = rental.payment_frequency;
+        amendment.new_rent_amount = new_rent_amount;
+        amendment.new_payment_frequency = new_payment_frequency;
+        amendment.status = AmendmentStatus::Proposed;
+        amendment.proposed_at = Clock::get()?.unix_timestamp;
+        amendment.signed_by = Pubkey::default();
+        amendment.effective_at = 0;
+        amendment.bump = *ctx.bumps.get("amendment").unwrap();
 
-    pub system_program: Program<'info, System>,
-}
+        rental.amendment_count = rental.amendment_count.checked_add(1).unwrap();
 
-#[derive(Accounts)]
-pub struct PayRent<'info> {
-    #[account(mut)]
-    pub rental_agreement: Account<'info, RentalAgreement>,
+        msg!("Amendment {} proposed", amendment.amendment_number);
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub tenant_token_account: Account<'info, TokenAccount>,
+    /// Countersign an amendment; new terms apply from the next period boundary
+    pub fn accept_amendment(
+        ctx: Context<AcceptAmendment>,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+        let amendment = &mut ctx.accounts.amendment;
 
-    #[account(mut)]
-    pub landlord_token_account: Account<'info, TokenAccount>,
+        require!(rental.status == RentalStatus::Active, RentalError::InactiveAgreement);
+        require!(amendment.status == AmendmentStatus::Proposed, RentalError::AmendmentNotPending);
+        require!(
+            is_counterparty(
+                rental,
+                ctx.accounts.property_manager.as_deref(),
+                &amendment.proposed_by,
+                &ctx.accounts.signer.key()
+            ),
+            RentalError::Unauthorized
+        );
 
-    #[account(mut)]
-    pub tenant: Signer<'info>,
+        let current_time = Clock::get()?.unix_timestamp;
+        apply_due_amendment(rental, current_time);
+        require!(rental.pending_terms_effective_at == 0, RentalError::AmendmentAlreadyScheduled);
+        // Periods before the switch are charged at the old rent, so they must all be paid first
+        require!(tenants_current(rental, current_time), RentalError::TenantsInArrears);
 
-    pub token_program: Program<'info, Token>,
-}
+        let periods_elapsed = (current_time - rental.schedule_start) as u64 / rental.payment_frequency;
+        let next_boundary = periods_elapsed
+            .checked_add(1)
+            .unwrap()
+            .checked_mul(rental.payment_frequency)
+            .unwrap();
+        let effective_at = rental.schedule_start.checked_add(next_boundary as i64).unwrap();
 
-#[derive(Accounts)]
-pub struct TerminateRental<'info> {
-    #[account(mut)]
-    pub rental_agreement: Account<'info, RentalAgreement>,
+        rental.pending_rent_amount = amendment.new_rent_amount;
+        rental.pending_payment_frequency = amendment.new_payment_frequency;
+        rental.pending_terms_effective_at = effective_at;
 
-    pub authority: Signer<'info>,
-}
+        amendment.status = AmendmentStatus::Accepted;
+        amendment.signed_by = ctx.accounts.signer.key();
+        amendment.effective_at = effective_at;
 
-#[derive(Accounts)]
-pub struct DistributeIncome<'info> {
-    pub rental_agreement: Account<'info, RentalAgreement>,
+        msg!("Amendment {} accepted, effective at {}", amendment.amendment_number, effective_at);
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub landlord_token_account: Account<'info, TokenAccount>,
+    /// Reject a proposed amendment, or withdraw it as the proposer
+    pub fn reject_amendment(
+        ctx: Context<RejectAmendment>,
+    ) -> Result<()> {
+        let rental = &ctx.accounts.rental_agreement;
+        let amendment = &mut ctx.accounts.amendment;
+        let signer = ctx.accounts.signer.key();
 
-    #[account(mut)]
-    pub holder_token_account: Account<'info, TokenAccount>,
+        require!(amendment.status == AmendmentStatus::Proposed, RentalError::AmendmentNotPending);
+        require!(
+            amendment.proposed_by == signer
+                || is_counterparty(rental, ctx.accounts.property_manager.as_deref(), &amendment.proposed_by, &signer),
+            RentalError::Unauthorized
+        );
 
-    pub landlord: Signer<'info>,
+        amendment.status = AmendmentStatus::Rejected;
+        amendment.signed_by = signer;
 
-    pub token_program: Program<'info, Token>,
-}
+        msg!("Amendment {} rejected", amendment.amendment_number);
+        Ok(())
+    }
+
+    /// Give notice of termination; the agreement ends once the notice period elapses
+    pub fn give_notice(
+        ctx: Context<GiveNotice>,
+        reason: TerminationReason,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+
+        require!(rental.status == RentalStatus::Active, RentalError::InactiveAgreement);
+        require!(
+            is_party(rental, ctx.accounts.property_manager.as_deref(), &ctx.accounts.authority.key()),
+            RentalError::Unauthorized
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        rental.status = RentalStatus::NoticeGiven;
+        rental.termination_reason = Some(reason);
+        rental.notice_given_by = ctx.accounts.authority.key();
+        rental.termination_date = current_time.checked_add(rental.notice_period).unwrap();
+
+        msg!("Termination notice given, effective at {}", rental.termination_date);
+        Ok(())
+    }
+
+    /// Dispute a termination notice so the arbiter must rule before settlement
+    pub fn raise_dispute(
+        ctx: Context<RaiseDispute>,
+        claim: String,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+
+        require!(rental.status == RentalStatus::NoticeGiven, RentalError::NoPendingTermination);
+        require!(claim.len() <= 200, RentalError::ClaimTooLong);
+        require!(
+            is_party(rental, ctx.accounts.property_manager.as_deref(), &ctx.accounts.authority.key()),
+            RentalError::Unauthorized
+        );
+
+        rental.status = RentalStatus::Disputed;
+        rental.dispute_claim = Some(claim);
+
+        msg!("Termination disputed by {}", ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    /// Rule on a disputed termination (by the arbiter)
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        uphold_termination: bool,
+        landlord_award: u64, // share of the deposit awarded to the landlord
+    ) -> Result<()> {
+        let config = &ctx.accounts.rental_config;
+        let rental = &mut ctx.accounts.rental_agreement;
+
+        require!(rental.status == RentalStatus::Disputed, RentalError::NotDisputed);
+        require_keys_eq!(config.arbiter, ctx.accounts.arbiter.key(), RentalError::Unauthorized);
+
+        if uphold_termination {
+            require!(landlord_award <= rental.security_deposit, RentalError::AwardExceedsDeposit);
+
+            rental.status = RentalStatus::Resolved;
+            rental.landlord_award = landlord_award;
+        } else {
+            rental.status = RentalStatus::Active;
+            rental.termination_reason = None;
+            rental.notice_given_by = Pubkey::default();
+            rental.termination_date = 0;
+        }
+
+        msg!("Dispute resolved, termination upheld: {}", uphold_termination);
+        Ok(())
+    }
+
+    /// Settle the deposit against arrears and end the agreement
+    pub fn settle_rental(
+        ctx: Context<SettleRental>,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental_agreement;
+
+        require!(
+            rental.status == RentalStatus::NoticeGiven || rental.status == RentalStatus::Resolved,
+            RentalError::NoPendingTermination
+        );
+        require!(
+            is_party(rental, ctx.accounts.property_manager.as_deref(), &ctx.accounts.authority.key()),
+            RentalError::Unauthorized
+        );
+        require_keys_eq!(ctx.accounts.landlord_token_account.owner, rental.landlord, RentalError::InvalidTokenAccount);
+        require_keys_eq!(ctx.accounts.landlord_token_account.mint, rental.payment_mint, RentalError::InvalidTokenAccount);
+        require_keys_eq!(ctx.accounts.tenant_token_account.owner, rental.tenant, RentalError::InvalidTokenAccount);
+        require_keys_eq!(ctx.accounts.tenant_token_account.mint, rental.payment_mint, RentalError::InvalidTokenAccount);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(current_time >= rental.termination_date, RentalError::NoticePeriodNotElapsed);
+        require!(
+            rental.stream_started_at == 0 || rental.stream_stopped_at != 0,
+            RentalError::StreamStillRunning
+        );
+
+        // Arrears are charged against the deposit unless the arbiter set the award
+        // The period starting on the termination date itself is not owed
+        let termination_date = rental.termination_date;
+        apply_due_amendment(rental, termination_date);
+        let arrears = rent_arrears(rental, termination_date - 1);
+        let deposit_balance = ctx.accounts.deposit_vault.amount;
+        let landlord_share = if rental.status == RentalStatus::Resolved {
+            rental.landlord_award.min(deposit_balance)
+        } else {
+            arrears.min(deposit_balance)
+        };
+        let tenant_share = deposit_balance.checked_sub(landlord_share).unwrap();
+
+        let lease_number = rental.lease_number.to_le_bytes();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"rental",
+            rental.landlord.as_ref(),
+            rental.tenant.as_ref(),
+            rental.property_id.as_ref(),
+            lease_number.as_ref(),
+            &[rental.bump],
+        ]];
+
+        if landlord_share > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.deposit_vault.to_account_info(),
+                        to: ctx.accounts.landlord_token_account.to_account_info(),
+                        authority: rental.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                landlord_share,
+            )?;
+        }
+
+        if tenant_share > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.deposit_vault.to_account_info(),
+                        to: ctx.accounts.tenant_token_account.to_account_info(),
+                        authority: rental.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                tenant_share,
+            )?;
+        }
+
+        // Return the escrow's rent to the landlord who funded it
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.deposit_vault.to_account_info(),
+                destination: ctx.accounts.landlord.to_account_info(),
+                authority: rental.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        rental.unpaid_arrears = arrears.saturating_sub(landlord_share);
+        rental.is_active = false;
+        rental.status = RentalStatus::Terminated;
+
+        let registry = &mut ctx.accounts.rental_registry;
+        accrue_vacancy(registry, current_time);
+        registry.active_agreements = registry.active_agreements.checked_sub(1).unwrap();
+
+        msg!(
+            "Rental settled: {} to landlord, {} refunded, {} arrears unpaid",
+            landlord_share,
+            tenant_share,
+            rental.unpaid_arrears
+        );
+        Ok(())
+    }
+
+    /// Close a settled agreement and return its rent to the landlord
+    pub fn close_rental_agreement(
+        ctx: Context<CloseRentalAgreement>,
+    ) -> Result<()> {
+        let rental = &ctx.accounts.rental_agreement;
+
+        require!(rental.status == RentalStatus::Terminated, RentalError::AgreementNotSettled);
+        require!(
+            is_landlord_side(rental, ctx.accounts.property_manager.as_deref(), &ctx.accounts.authority.key()),
+            RentalError::Unauthorized
+        );
+
+        msg!("Rental agreement {} closed", rental.lease_number);
+        Ok(())
+    }
+
+    /// Distribute rental income to property token holders
+    pub fn distribute_rental_income(
+        ctx: Context<DistributeIncome>,
+        amount: u64,
+    ) -> Result<()> {
+        let rental = &ctx.accounts.rental_agreement;
+
+        require!(rental.is_active, RentalError::InactiveAgreement);
+        require_keys_eq!(rental.landlord, ctx.accounts.landlord.key(), RentalError::Unauthorized);
+
+        // Transfer USDC from landlord to token holder
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.landlord_token_account.to_account_info(),
+                    to: ctx.accounts.holder_token_account.to_account_info(),
+                    authority: ctx.accounts.landlord.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        msg!("Distributed {} USDC to token holder", amount);
+        Ok(())
+    }
+}
+
+/// Add the time units have stood empty since the last registry update
+fn accrue_vacancy(registry: &mut PropertyRentalRegistry, now: i64) {
+    let elapsed = now.saturating_sub(registry.last_update).max(0) as u64;
+    let vacant_units = registry.total_units.saturating_sub(registry.active_agreements) as u64;
+
+    registry.vacant_unit_seconds = registry
+        .vacant_unit_seconds
+        .checked_add(vacant_units.checked_mul(elapsed).unwrap())
+        .unwrap();
+    registry.last_update = now;
+}
+
+/// Rent for periods due by `until` that has not been collected; rent is due at the start
+/// of each period, as in `period_due_date`
+fn rent_arrears(rental: &RentalAgreement, until: i64) -> u64 {
+    let rent_due = match rental.rent_mode {
+        RentMode::Periodic => {
+            periods_due(rental, until)
+                .checked_mul(rental.rent_amount)
+                .unwrap()
+                .checked_add(rental.rent_due_before_schedule)
+                .unwrap()
+        }
+        // Only rent streamed beyond what the escrow covered is owed
+        RentMode::Streaming => streamed_rent(rental, until).saturating_sub(rental.stream_deposited),
+    };
+
+    rent_due.saturating_sub(rental.rent_collected)
+}
+
+/// Periods of the current schedule whose rent has fallen due by `until`
+fn periods_due(rental: &RentalAgreement, until: i64) -> u64 {
+    if until < rental.schedule_start {
+        return 0;
+    }
+    ((until - rental.schedule_start) as u64 / rental.payment_frequency).checked_add(1).unwrap()
+}
+
+/// Whether every co-tenant has paid each periodic rent period due by `now`
+fn tenants_current(rental: &RentalAgreement, now: i64) -> bool {
+    if rental.rent_mode != RentMode::Periodic {
+        return true;
+    }
+    let periods = rental.periods_before_schedule.checked_add(periods_due(rental, now)).unwrap();
+    rental.tenants.iter().all(|share| share.total_payments >= periods)
+}
+
+/// Rent accrued by the stream up to `until`, not capped by the escrow balance.
+/// Accrual ends when the stream is stopped or the notice period runs out.
+fn streamed_rent(rental: &RentalAgreement, until: i64) -> u64 {
+    if rental.stream_started_at == 0 {
+        return 0;
+    }
+
+    let mut end = until;
+    if rental.stream_stopped_at != 0 {
+        end = end.min(rental.stream_stopped_at);
+    }
+    if rental.termination_date != 0 {
+        end = end.min(rental.termination_date);
+    }
+    let elapsed = end.saturating_sub(rental.stream_started_at).max(0) as u64;
+
+    let accrued = (elapsed as u128)
+        .checked_mul(rental.rent_amount as u128)
+        .unwrap()
+        .checked_div(rental.payment_frequency as u128)
+        .unwrap() as u64;
+
+    rental.stream_accrued_before.checked_add(accrued).unwrap()
+}
+
+/// Pay streamed rent out of escrow to the landlord, splitting off the management fee
+fn pay_out_stream<'info>(
+    rental: &Account<'info, RentalAgreement>,
+    token_program: &Program<'info, Token>,
+    stream_vault: &Account<'info, TokenAccount>,
+    landlord_token_account: &Account<'info, TokenAccount>,
+    manager_token_account: Option<&Account<'info, TokenAccount>>,
+    property_manager: Option<&PropertyManager>,
+    amount: u64,
+) -> Result<u64> {
+    require_keys_eq!(landlord_token_account.owner, rental.landlord, RentalError::InvalidTokenAccount);
+    require_keys_eq!(landlord_token_account.mint, rental.payment_mint, RentalError::InvalidTokenAccount);
+
+    let lease_number = rental.lease_number.to_le_bytes();
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"rental",
+        rental.landlord.as_ref(),
+        rental.tenant.as_ref(),
+        rental.property_id.as_ref(),
+        lease_number.as_ref(),
+        &[rental.bump],
+    ]];
+
+    let management_fee = management_fee(rental, property_manager, amount);
+
+    if management_fee > 0 {
+        let manager_token_account = manager_token_account.ok_or(RentalError::InvalidTokenAccount)?;
+        require!(rental.manager == Some(manager_token_account.owner), RentalError::InvalidTokenAccount);
+        require_keys_eq!(manager_token_account.mint, rental.payment_mint, RentalError::InvalidTokenAccount);
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: stream_vault.to_account_info(),
+                    to: manager_token_account.to_account_info(),
+                    authority: rental.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            management_fee,
+        )?;
+    }
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: stream_vault.to_account_info(),
+                to: landlord_token_account.to_account_info(),
+                authority: rental.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount.checked_sub(management_fee).unwrap(),
+    )?;
+
+    Ok(management_fee)
+}
+
+/// Start of the given payment period under the current schedule
+fn period_due_date(rental: &RentalAgreement, period: u64) -> i64 {
+    let periods_into_schedule = period.saturating_sub(rental.periods_before_schedule);
+
+    rental
+        .schedule_start
+        .checked_add(periods_into_schedule.checked_mul(rental.payment_frequency).unwrap() as i64)
+        .unwrap()
+}
+
+/// Switch to accepted amendment terms once their period boundary has passed
+fn apply_due_amendment(rental: &mut RentalAgreement, now: i64) {
+    if rental.pending_terms_effective_at == 0 || now < rental.pending_terms_effective_at {
+        return;
+    }
+
+    // Bank the rent owed under the old terms before restarting the schedule
+    if rental.stream_started_at != 0 {
+        rental.stream_accrued_before = streamed_rent(rental, rental.pending_terms_effective_at);
+        rental.stream_started_at = rental.pending_terms_effective_at;
+    }
+
+    let periods = (rental.pending_terms_effective_at - rental.schedule_start) as u64 / rental.payment_frequency;
+    rental.rent_due_before_schedule = rental
+        .rent_due_before_schedule
+        .checked_add(periods.checked_mul(rental.rent_amount).unwrap())
+        .unwrap();
+    rental.periods_before_schedule = rental.periods_before_schedule.checked_add(periods).unwrap();
+    rental.schedule_start = rental.pending_terms_effective_at;
+    rental.rent_amount = rental.pending_rent_amount;
+    rental.payment_frequency = rental.pending_payment_frequency;
+
+    rental.pending_rent_amount = 0;
+    rental.pending_payment_frequency = 0;
+    rental.pending_terms_effective_at = 0;
+}
+
+/// Convert an amount of the agreed mint into `mint`, rounding up in the landlord's favour
+fn convert_rent(rental: &RentalAgreement, mint: &Pubkey, amount: u64) -> Result<u64> {
+    if *mint == rental.payment_mint {
+        return Ok(amount);
+    }
+
+    let accepted = rental
+        .accepted_mints
+        .iter()
+        .find(|accepted| accepted.mint == *mint)
+        .ok_or(RentalError::InvalidPaymentMint)?;
+
+    let converted = (amount as u128)
+        .checked_mul(accepted.rate as u128)
+        .unwrap()
+        .checked_add(RATE_SCALE as u128 - 1)
+        .unwrap()
+        .checked_div(RATE_SCALE as u128)
+        .unwrap();
+
+    u64::try_from(converted).map_err(|_| error!(RentalError::InvalidAcceptedMints))
+}
+
+/// Per-period rent owed by the tenant at `index`; rounding dust goes to the primary tenant
+fn tenant_rent_share(rental: &RentalAgreement, index: usize) -> u64 {
+    let split = |share: &TenantShare| {
+        (rental.rent_amount as u128)
+            .checked_mul(share.share_bps as u128)
+            .unwrap()
+            .checked_div(10000)
+            .unwrap() as u64
+    };
+
+    let amount = split(&rental.tenants[index]);
+    if index == 0 {
+        let allocated = rental.tenants.iter().map(split).sum::<u64>();
+        amount.checked_add(rental.rent_amount - allocated).unwrap()
+    } else {
+        amount
+    }
+}
+
+/// The agreement's manager, as long as their approval on the property has not been revoked
+fn active_manager(rental: &RentalAgreement, property_manager: Option<&PropertyManager>) -> Option<Pubkey> {
+    property_manager
+        .filter(|approval| approval.is_active && rental.manager == Some(approval.manager))
+        .map(|approval| approval.manager)
+}
+
+/// Manager's cut of `amount`; nothing is owed to a revoked manager
+fn management_fee(rental: &RentalAgreement, property_manager: Option<&PropertyManager>, amount: u64) -> u64 {
+    if active_manager(rental, property_manager).is_none() {
+        return 0;
+    }
+
+    (amount as u128)
+        .checked_mul(rental.management_fee_bps as u128)
+        .unwrap()
+        .checked_div(10000)
+        .unwrap() as u64
+}
+
+/// Landlord, their active manager, or any co-tenant
+fn is_party(rental: &RentalAgreement, property_manager: Option<&PropertyManager>, key: &Pubkey) -> bool {
+    is_landlord_side(rental, property_manager, key) || rental.tenants.iter().any(|share| share.tenant == *key)
+}
+
+fn is_landlord_side(rental: &RentalAgreement, property_manager: Option<&PropertyManager>, key: &Pubkey) -> bool {
+    rental.landlord == *key || active_manager(rental, property_manager) == Some(*key)
+}
+
+/// The primary tenant countersigns for the tenants; the landlord or active manager for the landlord
+fn is_counterparty(
+    rental: &RentalAgreement,
+    property_manager: Option<&PropertyManager>,
+    proposer: &Pubkey,
+    signer: &Pubkey,
+) -> bool {
+    if rental.landlord == *proposer || rental.manager == Some(*proposer) {
+        rental.tenant == *signer
+    } else {
+        is_landlord_side(rental, property_manager, signer)
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeRentalConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RentalConfig::INIT_SPACE,
+        seeds = [b"rental_config", authority.key().as_ref()],
+        bump
+    )]
+    pub rental_config: Account<'info, RentalConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetArbiter<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental_config", authority.key().as_ref()],
+        bump = rental_config.bump,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub rental_config: Account<'info, RentalConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRentalRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PropertyRentalRegistry::INIT_SPACE,
+        seeds = [b"rental_registry", property.key().as_ref()],
+        bump
+    )]
+    pub rental_registry: Account<'info, PropertyRentalRegistry>,
+
+    pub property: Account<'info, Property>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRentalUnits<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental_registry", property.key().as_ref()],
+        bump = rental_registry.bump
+    )]
+    pub rental_registry: Account<'info, PropertyRentalRegistry>,
+
+    pub property: Account<'info, Property>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRentalAgreement<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RentalAgreement::INIT_SPACE,
+        seeds = [
+            b"rental",
+            landlord.key().as_ref(),
+            tenant.key().as_ref(),
+            property.key().as_ref(),
+            rental_registry.total_agreements.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub property: Account<'info, Property>,
+
+    #[account(
+        mut,
+        seeds = [b"rental_registry", property.key().as_ref()],
+        bump = rental_registry.bump
+    )]
+    pub rental_registry: Account<'info, PropertyRentalRegistry>,
+
+    /// Config whose arbiter rules on this agreement's disputes
+    pub rental_config: Account<'info, RentalConfig>,
+
+    /// Manager approval, required when a manager signs for the landlord
+    #[account(
+        seeds = [b"manager", property.key().as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = rental_agreement,
+        seeds = [b"deposit", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub deposit_vault: Account<'info, TokenAccount>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    /// CHECK: Landlord must be the property authority
+    #[account(address = property.authority @ RentalError::NotPropertyAuthority)]
+    pub landlord: AccountInfo<'info>,
+
+    /// Landlord or their approved manager
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Tenant address
+    pub tenant: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct PaySecurityDeposit<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(
+        mut,
+        seeds = [b"deposit", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub deposit_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub tenant_token_account: Account<'info, TokenAccount>,
+
+    pub tenant: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(period: u64)]
+pub struct PayRent<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required to pay the agreement's manager their fee
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental_registry", rental_agreement.property_id.as_ref()],
+        bump = rental_registry.bump
+    )]
+    pub rental_registry: Account<'info, PropertyRentalRegistry>,
+
+    #[account(
+        init,
+        payer = tenant,
+        space = 8 + RentReceipt::INIT_SPACE,
+        seeds = [b"receipt", rental_agreement.key().as_ref(), tenant.key().as_ref(), period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub rent_receipt: Account<'info, RentReceipt>,
+
+    #[account(mut)]
+    pub tenant_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub landlord_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub manager_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundStream<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(
+        init_if_needed,
+        payer = tenant,
+        token::mint = payment_mint,
+        token::authority = rental_agreement,
+        seeds = [b"stream", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub stream_vault: Account<'info, TokenAccount>,
+
+    #[account(address = rental_agreement.payment_mint)]
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub tenant_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStreamedRent<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs or takes a fee
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental_registry", rental_agreement.property_id.as_ref()],
+        bump = rental_registry.bump
+    )]
+    pub rental_registry: Account<'info, PropertyRentalRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"stream", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub stream_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub landlord_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub manager_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Landlord or their manager
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StopStream<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs or takes a fee
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental_registry", rental_agreement.property_id.as_ref()],
+        bump = rental_registry.bump
+    )]
+    pub rental_registry: Account<'info, PropertyRentalRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"stream", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub stream_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub landlord_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub manager_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub tenant_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Tenant receives the escrow's rent
+    #[account(mut, address = rental_agreement.tenant)]
+    pub tenant: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveRentalManager<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub landlord: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddGuarantor<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(mut)]
+    pub guarantor_token_account: Account<'info, TokenAccount>,
+
+    pub guarantor: Signer<'info>,
+    pub tenant: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFromGuarantor<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(
+        mut,
+        seeds = [b"rental_registry", rental_agreement.property_id.as_ref()],
+        bump = rental_registry.bump
+    )]
+    pub rental_registry: Account<'info, PropertyRentalRegistry>,
+
+    #[account(mut)]
+    pub guarantor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub landlord_token_account: Account<'info, TokenAccount>,
+
+    pub landlord: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAmendment<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs for the landlord
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + RentalAmendment::INIT_SPACE,
+        seeds = [b"amendment", rental_agreement.key().as_ref(), rental_agreement.amendment_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub amendment: Account<'info, RentalAmendment>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAmendment<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs for the landlord
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    #[account(mut, has_one = rental_agreement)]
+    pub amendment: Account<'info, RentalAmendment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RejectAmendment<'info> {
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs for the landlord
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    #[account(mut, has_one = rental_agreement)]
+    pub amendment: Account<'info, RentalAmendment>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GiveNotice<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs for the landlord
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs for the landlord
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(address = rental_agreement.rental_config @ RentalError::InvalidRentalConfig)]
+    pub rental_config: Account<'info, RentalConfig>,
+
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub arbiter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRental<'info> {
+    #[account(mut)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs for the landlord
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental_registry", rental_agreement.property_id.as_ref()],
+        bump = rental_registry.bump
+    )]
+    pub rental_registry: Account<'info, PropertyRentalRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"deposit", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub deposit_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub landlord_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub tenant_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Landlord receives the escrow's rent
+    #[account(mut, address = rental_agreement.landlord)]
+    pub landlord: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRentalAgreement<'info> {
+    #[account(mut, close = landlord)]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// Manager approval, required when the agreement's manager signs for the landlord
+    #[account(
+        seeds = [b"manager", rental_agreement.property_id.as_ref(), property_manager.manager.as_ref()],
+        bump = property_manager.bump,
+        seeds::program = property_tokenization::ID
+    )]
+    pub property_manager: Option<Account<'info, PropertyManager>>,
+
+    /// CHECK: Landlord receives the agreement's rent
+    #[account(mut, address = rental_agreement.landlord)]
+    pub landlord: AccountInfo<'info>,
+
+    /// Landlord or their manager
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeIncome<'info> {
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(mut)]
+    pub landlord_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    pub landlord: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RentalConfig {
+    pub authority: Pubkey,
+    pub arbiter: Pubkey,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PropertyRentalRegistry {
+    pub property: Pubkey,
+    pub total_units: u32,
+    pub active_agreements: u32,
+    pub total_agreements: u64,
+    pub vacant_unit_seconds: u64, // divide by 86400 for vacant unit-days
+    pub cumulative_rent_collected: u64,
+    pub last_update: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
 
 #[account]
 #[derive(InitSpace)]
 pub struct RentalAgreement {
     pub landlord: Pubkey,
     pub tenant: Pubkey,
+    pub lease_number: u64,
+    #[max_len(4)]
+    pub tenants: Vec<TenantShare>,
+    pub guarantor: Option<Pubkey>,
+    pub guarantee_limit: u64,
+    pub guarantor_paid: u64,
+    pub manager: Option<Pubkey>,
+    pub management_fee_bps: u16,
     pub property_id: Pubkey,
+    pub rental_config: Pubkey,
+    pub payment_mint: Pubkey,
+    #[max_len(3)]
+    pub accepted_mints: Vec<AcceptedMint>,
     pub rent_amount: u64,
     pub payment_frequency: u64,
+    pub rent_mode: RentMode,
+    pub stream_started_at: i64,
+    pub stream_stopped_at: i64,
+    pub stream_deposited: u64,
+    pub stream_withdrawn: u64,
+    pub stream_accrued_before: u64,
+    pub security_deposit: u64,
+    pub deposit_paid: bool,
+    pub notice_period: i64,
     pub last_payment_date: i64,
     pub total_payments: u64,
+    pub rent_collected: u64,
     pub is_active: bool,
+    pub status: RentalStatus,
+    pub termination_reason: Option<TerminationReason>,
+    pub notice_given_by: Pubkey,
+    pub termination_date: i64,
+    #[max_len(200)]
+    pub dispute_claim: Option<String>,
+    pub landlord_award: u64,
+    pub unpaid_arrears: u64,
+    pub amendment_count: u32,
+    pub pending_rent_amount: u64,
+    pub pending_payment_frequency: u64,
+    pub pending_terms_effective_at: i64,
     pub created_at: i64,
+    pub schedule_start: i64,
+    pub periods_before_schedule: u64,
+    pub rent_due_before_schedule: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RentalAmendment {
+    pub rental_agreement: Pubkey,
+    pub amendment_number: u32,
+    pub proposed_by: Pubkey,
+    pub previous_rent_amount: u64,
+    pub previous_payment_frequency: u64,
+    pub new_rent_amount: u64,
+    pub new_payment_frequency: u64,
+    pub status: AmendmentStatus,
+    pub proposed_at: i64,
+    pub signed_by: Pubkey,
+    pub effective_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RentReceipt {
+    pub rental_agreement: Pubkey,
+    pub tenant: Pubkey,
+    pub period: u64,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub amount_paid: u64,
+    pub due_date: i64,
+    pub paid_at: i64,
+    pub is_late: bool,
     pub bump: u8,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct TenantShare {
+    pub tenant: Pubkey,
+    pub share_bps: u16,
+    pub last_payment_date: i64,
+    pub total_payments: u64,
+    pub amount_paid: u64,
+    pub late_payments: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct AcceptedMint {
+    pub mint: Pubkey,
+    pub rate: u64, // units of this mint per unit of the agreed mint, scaled by RATE_SCALE
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TenantSplit {
+    pub tenant: Pubkey,
+    pub share_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum RentalStatus {
+    Active,
+    NoticeGiven,
+    Disputed,
+    Resolved,
+    Terminated,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum RentMode {
+    Periodic,
+    Streaming, // rent accrues per second from an escrowed balance
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum AmendmentStatus {
+    Proposed,
+    Accepted,
+    Rejected,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum TerminationReason {
+    EndOfTerm,
+    NonPayment,
+    BreachOfContract,
+    PropertySale,
+    TenantRelocation,
+    MutualAgreement,
+    Other,
+}
+
+#[event]
+pub struct RentPaid {
+    pub rental_agreement: Pubkey,
+    pub tenant: Pubkey,
+    pub period: u64,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub amount_paid: u64,
+    pub paid_at: i64,
+    pub is_late: bool,
+}
+
 #[error_code]
 pub enum RentalError {
     #[msg("Rental agreement is not active")]
@@ -201,4 +1972,229 @@ pub enum RentalError {
     PaymentNotDue,
     #[msg("Unauthorized access")]
     Unauthorized,
+    #[msg("Payment frequency must be greater than zero")]
+    InvalidPaymentFrequency,
+    #[msg("Notice period cannot be negative")]
+    InvalidNoticePeriod,
+    #[msg("Security deposit has already been paid")]
+    DepositAlreadyPaid,
+    #[msg("No termination is pending")]
+    NoPendingTermination,
+    #[msg("Rental agreement is not disputed")]
+    NotDisputed,
+    #[msg("Dispute claim is too long")]
+    ClaimTooLong,
+    #[msg("Award exceeds the security deposit")]
+    AwardExceedsDeposit,
+    #[msg("Notice period has not elapsed")]
+    NoticePeriodNotElapsed,
+    #[msg("Token account does not match the agreement")]
+    InvalidTokenAccount,
+    #[msg("Rental config does not match the agreement")]
+    InvalidRentalConfig,
+    #[msg("Landlord is not the property authority or an approved manager")]
+    NotPropertyAuthority,
+    #[msg("Tenant rent splits are invalid")]
+    InvalidTenantSplits,
+    #[msg("A guarantor is already set")]
+    GuarantorAlreadySet,
+    #[msg("Claim exceeds the rent in arrears")]
+    ClaimExceedsArrears,
+    #[msg("Claim exceeds the guarantee limit")]
+    ClaimExceedsGuarantee,
+    #[msg("Payment period does not match the next unpaid period")]
+    InvalidPeriod,
+    #[msg("Amendment is not awaiting a signature")]
+    AmendmentNotPending,
+    #[msg("Another amendment is already scheduled")]
+    AmendmentAlreadyScheduled,
+    #[msg("Every co-tenant must be current on rent before terms change")]
+    TenantsInArrears,
+    #[msg("Accepted mints are invalid")]
+    InvalidAcceptedMints,
+    #[msg("Mint is not accepted for this agreement")]
+    InvalidPaymentMint,
+    #[msg("Unit count is invalid")]
+    InvalidUnits,
+    #[msg("No vacant units on this property")]
+    NoVacantUnits,
+    #[msg("Instruction does not apply to this rent mode")]
+    WrongRentMode,
+    #[msg("Rent stream has been stopped")]
+    StreamStopped,
+    #[msg("Rent stream has not started")]
+    StreamNotStarted,
+    #[msg("Rent stream must be stopped first")]
+    StreamStillRunning,
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+    #[msg("Rental agreement has not been settled")]
+    AgreementNotSettled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEEK: u64 = 7 * 86400;
+
+    fn tenant(share_bps: u16) -> TenantShare {
+        TenantShare {
+            tenant: Pubkey::new_unique(),
+            share_bps,
+            last_payment_date: 0,
+            total_payments: 0,
+            amount_paid: 0,
+            late_payments: 0,
+        }
+    }
+
+    fn rental(rent_amount: u64, tenants: Vec<TenantShare>) -> RentalAgreement {
+        RentalAgreement {
+            landlord: Pubkey::new_unique(),
+            tenant: tenants[0].tenant,
+            lease_number: 0,
+            tenants,
+            guarantor: None,
+            guarantee_limit: 0,
+            guarantor_paid: 0,
+            manager: None,
+            management_fee_bps: 0,
+            property_id: Pubkey::default(),
+            rental_config: Pubkey::default(),
+            payment_mint: Pubkey::default(),
+            accepted_mints: Vec::new(),
+            rent_amount,
+            payment_frequency: WEEK,
+            rent_mode: RentMode::Periodic,
+            stream_started_at: 0,
+            stream_stopped_at: 0,
+            stream_deposited: 0,
+            stream_withdrawn: 0,
+            stream_accrued_before: 0,
+            security_deposit: 0,
+            deposit_paid: true,
+            notice_period: 0,
+            last_payment_date: 0,
+            total_payments: 0,
+            rent_collected: 0,
+            is_active: true,
+            status: RentalStatus::Active,
+            termination_reason: None,
+            notice_given_by: Pubkey::default(),
+            termination_date: 0,
+            dispute_claim: None,
+            landlord_award: 0,
+            unpaid_arrears: 0,
+            amendment_count: 0,
+            pending_rent_amount: 0,
+            pending_payment_frequency: 0,
+            pending_terms_effective_at: 0,
+            created_at: 0,
+            schedule_start: 0,
+            periods_before_schedule: 0,
+            rent_due_before_schedule: 0,
+            bump: 0,
+        }
+    }
+
+    /// Record a payment of every tenant's share for their next period
+    fn pay_all(rental: &mut RentalAgreement) {
+        for index in 0..rental.tenants.len() {
+            let amount = tenant_rent_share(rental, index);
+            rental.tenants[index].total_payments += 1;
+            rental.rent_collected += amount;
+        }
+        rental.total_payments = rental.tenants.iter().map(|share| share.total_payments).min().unwrap();
+    }
+
+    /// Schedule new terms from the given period boundary, as `accept_amendment` does
+    fn schedule(rental: &mut RentalAgreement, rent_amount: u64, payment_frequency: u64, effective_at: i64) {
+        rental.pending_rent_amount = rent_amount;
+        rental.pending_payment_frequency = payment_frequency;
+        rental.pending_terms_effective_at = effective_at;
+    }
+
+    #[test]
+    fn rent_is_due_at_the_start_of_each_period() {
+        let rental = rental(1_000, vec![tenant(10000)]);
+        let week = WEEK as i64;
+
+        assert_eq!(period_due_date(&rental, 0), 0);
+        assert_eq!(period_due_date(&rental, 2), 2 * week);
+        assert_eq!(rent_arrears(&rental, 0), 1_000);
+        assert_eq!(rent_arrears(&rental, 2 * week - 1), 2_000);
+        assert_eq!(rent_arrears(&rental, 2 * week), 3_000);
+    }
+
+    #[test]
+    fn rent_increase_charges_new_rent_only_after_the_switch() {
+        let mut rental = rental(1_000, vec![tenant(10000)]);
+        let week = WEEK as i64;
+        for _ in 0..3 {
+            pay_all(&mut rental);
+        }
+        assert!(tenants_current(&rental, 3 * week - 1));
+
+        schedule(&mut rental, 1_500, WEEK, 3 * week);
+        apply_due_amendment(&mut rental, 3 * week + 10);
+
+        assert_eq!(rental.periods_before_schedule, 3);
+        assert_eq!(rental.rent_due_before_schedule, 3_000);
+        assert_eq!(period_due_date(&rental, 3), 3 * week);
+        assert_eq!(period_due_date(&rental, 4), 4 * week);
+        assert_eq!(tenant_rent_share(&rental, 0), 1_500);
+        assert_eq!(rent_arrears(&rental, 3 * week + 10), 1_500);
+
+        pay_all(&mut rental);
+        assert_eq!(rent_arrears(&rental, 4 * week - 1), 0);
+    }
+
+    #[test]
+    fn rent_decrease_leaves_no_residual_arrears() {
+        let mut rental = rental(1_000, vec![tenant(6000), tenant(4000)]);
+        let week = WEEK as i64;
+        for _ in 0..2 {
+            pay_all(&mut rental);
+        }
+
+        // From period 2 the rent drops to 700 per fortnight
+        schedule(&mut rental, 700, 2 * WEEK, 2 * week);
+        apply_due_amendment(&mut rental, 2 * week);
+
+        assert_eq!(period_due_date(&rental, 2), 2 * week);
+        assert_eq!(period_due_date(&rental, 3), 4 * week);
+        assert_eq!(tenant_rent_share(&rental, 0), 420);
+        assert_eq!(tenant_rent_share(&rental, 1), 280);
+        assert_eq!(rent_arrears(&rental, 4 * week - 1), 700);
+
+        pay_all(&mut rental);
+        assert_eq!(rent_arrears(&rental, 4 * week - 1), 0);
+        assert!(tenants_current(&rental, 4 * week - 1));
+        assert!(!tenants_current(&rental, 4 * week));
+    }
+
+    #[test]
+    fn terms_cannot_switch_while_a_co_tenant_is_behind() {
+        let mut rental = rental(1_000, vec![tenant(5000), tenant(5000)]);
+        let week = WEEK as i64;
+        pay_all(&mut rental);
+        rental.tenants[0].total_payments += 1;
+
+        assert!(!tenants_current(&rental, week));
+        rental.tenants[1].total_payments += 1;
+        assert!(tenants_current(&rental, week));
+    }
+
+    #[test]
+    fn rounding_dust_goes_to_the_primary_tenant() {
+        let mut rental = rental(1_000, vec![tenant(3333), tenant(3333), tenant(3334)]);
+        pay_all(&mut rental);
+        schedule(&mut rental, 1_001, WEEK, WEEK as i64);
+        apply_due_amendment(&mut rental, WEEK as i64);
+
+        let shares: Vec<u64> = (0..3).map(|index| tenant_rent_share(&rental, index)).collect();
+        assert_eq!(shares, vec![335, 333, 333]);
+        assert_eq!(shares.iter().sum::<u64>(), 1_001);
+    }
 }